@@ -0,0 +1,354 @@
+use bevy::{
+    prelude::*,
+    time::Stopwatch,
+};
+
+const MARATHON_LINES: usize = 150;
+const SPRINT_LINES: usize = 40;
+const ULTRA_TIME_LIMIT: f32 = 120.0;
+const MASTER_LEVEL: usize = 999;
+const MASTER_SECTION_LEVELS: usize = 100;
+const CLASSIC_B_LINES: usize = 25;
+/// クラシックモードで選択できる開始レベルの数（0から19）
+const CLASSIC_LEVELS: usize = 20;
+/// クラシックBで選択できるおじゃまブロックの高さの数（0から5）
+const CLASSIC_HEIGHTS: usize = 6;
+/// 選択できるランダマイザの名前（`GeneratorKind::ALL`と同じ順番）
+const RANDOMIZER_NAMES: [&str; 7] = ["7バッグ", "14バッグ", "TGM", "TGM2", "TGM3", "NES", "ランダム"];
+
+/// 内部段位ごとに表示する段位（TGM2準拠）
+const GRADE_NAMES: [&str; 32] = [
+    "9", "8", "7", "6", "5", "4", "4", "3",
+    "3", "2", "2", "2", "1", "1", "1", "S1",
+    "S1", "S1", "S2", "S3", "S4", "S4", "S4", "S5",
+    "S5", "S6", "S6", "S7", "S7", "S8", "S8", "S9",
+];
+const GRADE_GM: &str = "GM";
+
+/// 遊ぶゲームモードを管理するリソース
+/// メインメニューで選択され、ゲーム開始時にルールへ反映される
+#[derive(Resource, Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum GameMode {
+    /// 150ライン消去でクリア、10ラインごとにレベルアップ
+    #[default]
+    Marathon,
+    /// 40ラインを消去するまでのタイムを競う
+    Sprint,
+    /// 2分間のスコアを競う
+    Ultra,
+    /// レベル999までの段位を競う（TGM準拠）
+    Master,
+    /// 盤面の下のおじゃまブロックを全て消去するまでのタイムと置いたブロックの数を競う
+    DigRace,
+    /// だんだん早くせり上がるおじゃまブロックの中で、生き残った時間を競う
+    Survival,
+    /// NES版テトリスのルールでスコアを競う（Aタイプ）
+    ClassicA,
+    /// NES版テトリスのルールで25ラインを消去するまでのスコアを競う（Bタイプ）
+    ClassicB,
+    /// マラソンのルールで、固定したブロックがだんだん見えなくなる
+    Fading,
+    /// マラソンのルールで、固定したブロックがすぐに見えなくなる
+    Invisible,
+    /// マスターのルールで、ブロックが縦横2倍の大きさになる（TGM準拠）
+    Big,
+}
+
+/// ゲームモードのクリア条件
+/// - Lines: 消去したライン数が値に達したらクリア
+/// - Time: 経過時間が値（秒）に達したらクリア（タイムアップ）
+/// - Level: レベルが値に達したらクリア
+/// - Garbage: 盤面のおじゃまブロックを全て消去したらクリア
+/// - Survive: クリア条件はなく、ゲームオーバーになるまで続ける
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Goal {
+    Lines(usize),
+    Time(f32),
+    Level(usize),
+    Garbage,
+    Survive,
+}
+
+/// メインメニューでゲームモードごとに選択できる設定
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ModeOption {
+    /// 開始レベル
+    Level,
+    /// 開始時に並べるおじゃまブロックの高さ
+    Height,
+    /// ブロックの出現順を決めるランダマイザ
+    Randomizer,
+}
+
+impl ModeOption {
+    /// メインメニューに表示する設定の名前を返すメソッド
+    pub fn name(&self) -> &'static str {
+        match self {
+            ModeOption::Level => "レベル",
+            ModeOption::Height => "たかさ",
+            ModeOption::Randomizer => "ツモ",
+        }
+    }
+
+    /// メインメニューに表示する設定の値を返すメソッド
+    pub fn value_name(&self, value: usize) -> String {
+        match self {
+            ModeOption::Level | ModeOption::Height => value.to_string(),
+            ModeOption::Randomizer => RANDOMIZER_NAMES[value].to_string(),
+        }
+    }
+
+    /// 選択できる値の数を返すメソッド（0から数える）
+    pub fn count(&self) -> usize {
+        match self {
+            ModeOption::Level => CLASSIC_LEVELS,
+            ModeOption::Height => CLASSIC_HEIGHTS,
+            ModeOption::Randomizer => RANDOMIZER_NAMES.len(),
+        }
+    }
+}
+
+/// メインメニューで選択されたゲームモードの設定を管理するリソース
+/// - level: 開始レベル
+/// - height: 開始時に並べるおじゃまブロックの高さ（段階）
+/// - randomizer: ランダマイザの番号（`GeneratorKind::ALL`の順番）
+#[derive(Resource, Debug, Default)]
+pub struct ModeOptions {
+    pub level: usize,
+    pub height: usize,
+    pub randomizer: usize,
+}
+
+impl ModeOptions {
+    /// 設定の値を返すメソッド
+    pub fn get(&self, option: ModeOption) -> usize {
+        match option {
+            ModeOption::Level => self.level,
+            ModeOption::Height => self.height,
+            ModeOption::Randomizer => self.randomizer,
+        }
+    }
+
+    /// 設定の値を順番に切り替えるメソッド
+    /// 端まで来たら反対側に戻る
+    ///
+    /// # Arguments
+    /// * option - 切り替える設定
+    /// * step - 進める数（負の値なら前に戻る）
+    pub fn cycle(&mut self, option: ModeOption, step: i32) {
+        let count = option.count() as i32;
+        let value = (self.get(option) as i32 + step).rem_euclid(count) as usize;
+        match option {
+            ModeOption::Level => self.level = value,
+            ModeOption::Height => self.height = value,
+            ModeOption::Randomizer => self.randomizer = value,
+        }
+    }
+}
+
+/// ゲームオーバー画面に表示する成績の種類
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Ranking {
+    Score,
+    Time,
+    Grade,
+    TimeAndPieces,
+}
+
+/// ゲーム中に画面の左右のボードへ表示する値
+/// 先頭の値はフィールドの左下に、それ以降はNEXTの下に順番に並べる
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum HudItem {
+    Score,
+    Level,
+    Lines,
+    LinesLeft,
+    Time,
+    TimeLeft,
+    Grade,
+    Section,
+    GarbageLeft,
+    Pieces,
+}
+
+impl HudItem {
+    /// ボード上部に表示するタイトルを返すメソッド
+    pub fn title(&self) -> &'static str {
+        match self {
+            HudItem::Score => "SCORE",
+            HudItem::Level => "LEVEL",
+            HudItem::Lines => "LINES",
+            HudItem::LinesLeft => "LEFT",
+            HudItem::Time => "TIME",
+            HudItem::TimeLeft => "TIME",
+            HudItem::Grade => "GRADE",
+            HudItem::Section => "LEVEL",
+            HudItem::GarbageLeft => "GARBAGE",
+            HudItem::Pieces => "PIECES",
+        }
+    }
+}
+
+impl GameMode {
+    /// メインメニューで選択できる全てのゲームモード
+    pub const ALL: [GameMode; 11] = [
+        GameMode::Marathon,
+        GameMode::Sprint,
+        GameMode::Ultra,
+        GameMode::Master,
+        GameMode::DigRace,
+        GameMode::Survival,
+        GameMode::ClassicA,
+        GameMode::ClassicB,
+        GameMode::Fading,
+        GameMode::Invisible,
+        GameMode::Big,
+    ];
+
+    /// ゲームモードの名前を返すメソッド
+    pub fn name(&self) -> &'static str {
+        match self {
+            GameMode::Marathon => "マラソン",
+            GameMode::Sprint => "スプリント",
+            GameMode::Ultra => "ウルトラ",
+            GameMode::Master => "マスター",
+            GameMode::DigRace => "ディグレース",
+            GameMode::Survival => "サバイバル",
+            GameMode::ClassicA => "クラシックA",
+            GameMode::ClassicB => "クラシックB",
+            GameMode::Fading => "フェード",
+            GameMode::Invisible => "インビジブル",
+            GameMode::Big => "ビッグ",
+        }
+    }
+
+    /// メインメニューに表示するゲームモードの説明を返すメソッド
+    pub fn description(&self) -> &'static str {
+        match self {
+            GameMode::Marathon => "150ラインけしたらクリア",
+            GameMode::Sprint => "40ラインけすまでのタイム",
+            GameMode::Ultra => "2ふんかんのスコア",
+            GameMode::Master => "レベル999までのだんい",
+            GameMode::DigRace => "おじゃまブロックをほりきるタイム",
+            GameMode::Survival => "せりあがるブロックにたえるタイム",
+            GameMode::ClassicA => "NESのルールでスコアをきそう",
+            GameMode::ClassicB => "NESのルールで25ラインけす",
+            GameMode::Fading => "おいたブロックがきえていくマラソン",
+            GameMode::Invisible => "おいたブロックがみえないマラソン",
+            GameMode::Big => "おおきなブロックでレベル999までのだんい",
+        }
+    }
+
+    /// ゲームモードのクリア条件を返すメソッド
+    pub fn goal(&self) -> Goal {
+        match self {
+            GameMode::Marathon | GameMode::Fading | GameMode::Invisible => Goal::Lines(MARATHON_LINES),
+            GameMode::Sprint => Goal::Lines(SPRINT_LINES),
+            GameMode::Ultra => Goal::Time(ULTRA_TIME_LIMIT),
+            GameMode::Master | GameMode::Big => Goal::Level(MASTER_LEVEL),
+            GameMode::DigRace => Goal::Garbage,
+            GameMode::Survival | GameMode::ClassicA => Goal::Survive,
+            GameMode::ClassicB => Goal::Lines(CLASSIC_B_LINES),
+        }
+    }
+
+    /// ゲームオーバー画面に表示する成績の種類を返すメソッド
+    pub fn ranking(&self) -> Ranking {
+        match self {
+            GameMode::Sprint | GameMode::Survival => Ranking::Time,
+            GameMode::Master | GameMode::Big => Ranking::Grade,
+            GameMode::DigRace => Ranking::TimeAndPieces,
+            _ => Ranking::Score,
+        }
+    }
+
+    /// ゲーム中に表示する値を返すメソッド
+    pub fn hud(&self) -> &'static [HudItem] {
+        match self {
+            GameMode::Marathon | GameMode::Fading | GameMode::Invisible => {
+                &[HudItem::Score, HudItem::Level, HudItem::Lines]
+            }
+            GameMode::Sprint => &[HudItem::Time, HudItem::LinesLeft, HudItem::Lines],
+            GameMode::Ultra => &[HudItem::Score, HudItem::TimeLeft, HudItem::Lines],
+            GameMode::Master | GameMode::Big => &[HudItem::Grade, HudItem::Section, HudItem::Time],
+            GameMode::DigRace => &[HudItem::GarbageLeft, HudItem::Time, HudItem::Pieces],
+            GameMode::Survival => &[HudItem::Time, HudItem::Lines, HudItem::Pieces],
+            GameMode::ClassicA => &[HudItem::Score, HudItem::Level, HudItem::Lines],
+            GameMode::ClassicB => &[HudItem::Score, HudItem::Level, HudItem::LinesLeft],
+        }
+    }
+
+    /// メインメニューで選択できる設定を返すメソッド
+    pub fn options(&self) -> &'static [ModeOption] {
+        match self {
+            GameMode::ClassicA => &[ModeOption::Level],
+            GameMode::ClassicB => &[ModeOption::Level, ModeOption::Height],
+            GameMode::Master | GameMode::Big => &[],
+            _ => &[ModeOption::Randomizer],
+        }
+    }
+
+    /// 選択できるゲームモードを順番に切り替えるメソッド
+    /// 端まで来たら反対側に戻る
+    ///
+    /// # Arguments
+    /// * step - 進める数（負の値なら前に戻る）
+    pub fn cycle(&self, step: i32) -> Self {
+        let count = Self::ALL.len() as i32;
+        let index = Self::ALL.iter().position(|mode| mode == self).unwrap_or(0) as i32;
+        Self::ALL[(index + step).rem_euclid(count) as usize]
+    }
+}
+
+/// ゲームを遊んでいる時間を管理するリソース
+/// ゲーム中だけ進み、ゲームオーバー画面では止まった時間を表示する
+#[derive(Resource, Default, Deref, DerefMut)]
+pub struct PlayTime(pub Stopwatch);
+
+/// マスターモードの段位を管理するリソース
+/// - internal: 内部段位（表示する段位は`GRADE_NAMES`で決まる）
+/// - points: 内部段位が上がるまでに貯める段位ポイント
+/// - decay: 段位ポイントが減るまでの経過時間（秒）
+/// - gm: GMの条件を満たしたか
+/// - gm_failed: 途中のGMの条件を満たせなかったか
+#[derive(Resource, Debug, Default)]
+pub struct Grade {
+    pub internal: usize,
+    pub points: usize,
+    pub decay: f32,
+    pub gm: bool,
+    pub gm_failed: bool,
+}
+
+impl Grade {
+    /// 表示する段位の名前を返すメソッド
+    pub fn name(&self) -> &'static str {
+        if self.gm {
+            GRADE_GM
+        } else {
+            GRADE_NAMES[self.internal.min(GRADE_NAMES.len() - 1)]
+        }
+    }
+
+    /// 内部段位の上限か判定するメソッド
+    pub fn is_max(&self) -> bool {
+        self.internal >= GRADE_NAMES.len() - 1
+    }
+}
+
+/// マスターモードのセクション（レベル100ごとの区切り）ごとにかかった時間（秒）を管理するリソース
+#[derive(Resource, Debug, Default, Deref, DerefMut)]
+pub struct SectionTimes(pub Vec<f32>);
+
+impl SectionTimes {
+    /// セクションが始まるレベルを返すメソッド
+    pub fn start_level(index: usize) -> usize {
+        index * MASTER_SECTION_LEVELS
+    }
+}
+
+/// 秒数を「分:秒.1/100秒」の形式の文字列に変換する関数
+pub fn format_time(seconds: f32) -> String {
+    let centis = (seconds.max(0.0) * 100.0).floor() as u64;
+    format!("{}:{:02}.{:02}", centis / 6000, centis / 100 % 60, centis % 100)
+}
@@ -7,6 +7,17 @@ use crate::{
     PATH_IMAGE_RETRY,
     AppState,
     Score,
+    PlacedPieces,
+    GameoverReason,
+    Seed,
+};
+use crate::gamemode::{
+    format_time,
+    GameMode,
+    Grade,
+    PlayTime,
+    Ranking,
+    SectionTimes,
 };
 
 const ROOT_WIDTH: Val = Val::Percent(100.0);
@@ -19,8 +30,19 @@ const BOARD_PADDING: Val = Val::Px(16.0);
 const BOARD_COLOR: Color = Color::srgb(0.13, 0.14, 0.24);
 
 const TITLE_TEXT: &str = "ゲームオーバー";
+const CLEAR_TEXT: &str = "クリア";
 
 const SCORE_TEXT: &str = "スコア";
+const TIME_TEXT: &str = "タイム";
+const GRADE_TEXT: &str = "だんい";
+const PIECES_TEXT: &str = "ブロック";
+
+const SECTION_FONT_SIZE: f32 = 12.0;
+/// セクションタイムを何行に分けて表示するか
+const SECTION_ROWS: usize = 5;
+
+const SEED_TEXT: &str = "シード";
+const SEED_FONT_SIZE: f32 = 16.0;
 
 const LIST_WIDTH: Val = Val::Px(BOARD_SIZE.x);
 const LIST_HEIGHT: Val = Val::Px(48.0);
@@ -94,19 +116,23 @@ impl Gameover {
     }
 
     /// ゲームオーバーメッセージを表示するテキストを生成します。
+    /// ゲームモードの名前と、クリアしたかどうかを表示します。
     ///
     /// Params:
     /// * `font`: テキストに使用するフォント
+    /// * `mode`: 遊んでいたゲームモード
+    /// * `clear`: ゲームモードをクリアしたか
     ///
     /// Returns:
     /// * `Self`: Gameoverのインスタンス。
     /// * `Text`: ゲームオーバーメッセージのテキスト。
     /// * `TextFont`: フォントスタイル。
     /// * `TextColor`: テキストの色
-    fn from_title(font: Handle<Font>) -> (Self, Text, TextFont, TextColor) {
+    fn from_title(font: Handle<Font>, mode: GameMode, clear: bool) -> (Self, Text, TextFont, TextColor) {
+        let result = if clear { CLEAR_TEXT } else { TITLE_TEXT };
         (
             Self,
-            Text::new(TITLE_TEXT),
+            Text::new(format!("{} {}", mode.name(), result)),
             TextFont {
                 font: font.clone(),
                 font_size: TEXT_FONT_SIZE,
@@ -116,17 +142,17 @@ impl Gameover {
         )
     }
 
-    /// ゲームオーバー画面に表示するスコア
+    /// ゲームオーバー画面に表示するゲームオーバーの原因
     ///
     /// Returns:
     /// * `Self`: Gameoverのインスタンス。
-    /// * `Text`: スコアと点数。
+    /// * `Text`: ゲームオーバーの原因。
     /// * `TextFont`: フォントスタイル。
     /// * `TextColor`: テキストの色
-    fn from_score(font: Handle<Font>, score: String) -> (Self, Text, TextFont, TextColor) {
+    fn from_reason(font: Handle<Font>, reason: GameoverReason) -> (Self, Text, TextFont, TextColor) {
         (
             Self,
-            Text::new(format!("{}  {}", SCORE_TEXT, score)),
+            Text::new(reason.message()),
             TextFont {
                 font: font.clone(),
                 font_size: TEXT_FONT_SIZE,
@@ -136,6 +162,84 @@ impl Gameover {
         )
     }
 
+    /// ゲームオーバー画面に表示する成績
+    /// ゲームモードによってスコアかタイムを表示します。
+    ///
+    /// Params:
+    /// * `label`: 成績の種類
+    /// * `value`: 成績の値
+    ///
+    /// Returns:
+    /// * `Self`: Gameoverのインスタンス。
+    /// * `Text`: 成績の種類と値。
+    /// * `TextFont`: フォントスタイル。
+    /// * `TextColor`: テキストの色
+    fn from_score(font: Handle<Font>, label: &str, value: String) -> (Self, Text, TextFont, TextColor) {
+        (
+            Self,
+            Text::new(format!("{}  {}", label, value)),
+            TextFont {
+                font: font.clone(),
+                font_size: TEXT_FONT_SIZE,
+                ..Default::default()
+            },
+            TextColor(TEXT_COLOR),
+        )
+    }
+
+    /// ゲームオーバー画面に表示するシード値
+    ///
+    /// Returns:
+    /// * `Self`: Gameoverのインスタンス。
+    /// * `Text`: シードとシード値。
+    /// * `TextFont`: フォントスタイル。
+    /// * `TextColor`: テキストの色
+    fn from_seed(font: Handle<Font>, seed: u64) -> (Self, Text, TextFont, TextColor) {
+        (
+            Self,
+            Text::new(format!("{}  {}", SEED_TEXT, seed)),
+            TextFont {
+                font: font.clone(),
+                font_size: SEED_FONT_SIZE,
+                ..Default::default()
+            },
+            TextColor(TEXT_COLOR),
+        )
+    }
+
+    /// ゲームオーバー画面に表示するセクションタイム
+    /// セクションごとの開始レベルとかかった時間を、2列に分けて表示します。
+    ///
+    /// Params:
+    /// * `sections`: セクションごとにかかった時間
+    ///
+    /// Returns:
+    /// * `Self`: Gameoverのインスタンス。
+    /// * `Text`: セクションタイムの一覧。
+    /// * `TextFont`: フォントスタイル。
+    /// * `TextColor`: テキストの色
+    fn from_sections(font: Handle<Font>, sections: &[f32]) -> (Self, Text, TextFont, TextColor) {
+        let section = |index: usize| {
+            sections.get(index).map_or(String::new(), |time| {
+                format!("{:03}  {}", SectionTimes::start_level(index), format_time(*time))
+            })
+        };
+        let text = (0..SECTION_ROWS.min(sections.len()))
+            .map(|row| format!("{}    {}", section(row), section(row + SECTION_ROWS)))
+            .collect::<Vec<_>>()
+            .join("\n");
+        (
+            Self,
+            Text::new(text),
+            TextFont {
+                font: font.clone(),
+                font_size: SECTION_FONT_SIZE,
+                ..Default::default()
+            },
+            TextColor(TEXT_COLOR),
+        )
+    }
+
     /// ゲームオーバー画面に表示するボタンの配置を決めるノード
     ///
     /// Returns:
@@ -207,19 +311,41 @@ impl Gameover {
 /// * root
 ///   * board
 ///     * gameover text
-///     * Score
+///     * gameover reason
+///     * Score, Time or Grade
+///     * Section times
+///     * Seed
 ///     * button list
 ///       * house button
 ///         * icon
 ///       * retry button
 ///         * icon
+#[allow(clippy::too_many_arguments)]
 fn setup(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     score: Res<Score>,
+    reason: Res<GameoverReason>,
+    seed: Res<Seed>,
+    mode: Res<GameMode>,
+    playtime: Res<PlayTime>,
+    grade: Res<Grade>,
+    sections: Res<SectionTimes>,
+    pieces: Res<PlacedPieces>,
 ) {
     info_once!("setup");
 
+    // ゲームモードの成績の種類に合わせて表示する
+    let (label, value) = match mode.ranking() {
+        Ranking::Score => (SCORE_TEXT, score.to_string()),
+        Ranking::Time => (TIME_TEXT, format_time(playtime.elapsed_secs())),
+        Ranking::Grade => (GRADE_TEXT, grade.name().to_string()),
+        Ranking::TimeAndPieces => (
+            TIME_TEXT,
+            format!("{}  {}{}", format_time(playtime.elapsed_secs()), pieces.0, PIECES_TEXT),
+        ),
+    };
+
     let font = asset_server.load(PATH_FONT);
     let house_image = asset_server.load(PATH_IMAGE_HOUSE);
     let retry_image = asset_server.load(PATH_IMAGE_RETRY);
@@ -229,8 +355,11 @@ fn setup(
         children![(
             Gameover::from_board(),
             children![
-                Gameover::from_title(font.clone()),
-                Gameover::from_score(font.clone(), score.to_string()),
+                Gameover::from_title(font.clone(), *mode, reason.is_clear()),
+                Gameover::from_reason(font.clone(), *reason),
+                Gameover::from_score(font.clone(), label, value),
+                Gameover::from_sections(font.clone(), &sections),
+                Gameover::from_seed(font.clone(), seed.value),
                 (Gameover::from_button_list(), children![
                     (Gameover::from_button(), Home, children![(
                         Gameover::from_icon(house_image.clone()),
@@ -246,6 +375,7 @@ fn setup(
 
 /// ホームボタンの挙動を決める関数
 /// ボタンが押されたらメインメニュー画面に戻リマス
+#[allow(clippy::type_complexity)]
 fn house_button_system(
     mut interaction_query: Query<
     (&Interaction, &mut BackgroundColor),
@@ -276,6 +406,7 @@ fn house_button_system(
 
 /// リトライボタンの挙動を決める関数
 /// ボタンが押されたらもう一度ゲームを遊ぶことができます
+#[allow(clippy::type_complexity)]
 fn retry_button_system(
     mut interaction_query: Query<
     (&Interaction, &mut BackgroundColor),
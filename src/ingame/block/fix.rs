@@ -1,112 +1,279 @@
 use bevy::prelude::*;
 
 use crate::{
-    GRID_SIZE,
     AppState,
-    Score,
+    GameoverReason,
+    Level,
 };
 use crate::ingame::{
-    BlockSpawned,
+    GamePhase,
     BlockFixed,
+    LinesCleared,
+    PerfectCleared,
+    Spin,
 };
 use crate::ingame::utils::prelude::*;
 
-/// ブロックの削除を管理する関数
-/// `FixEvent`を受け取り、プレイヤーブロックを固定ブロックに変換し、
-/// ブロックマップを更新して、ラインが揃った場合にブロックを削除します。
+/// Tスピンを判定する関数（3コーナールール）
+/// ブロックセットでTスピンの判定を行うと定義されたブロック（Tブロック）が対象
+/// 最後の操作が回転で、Tブロックの中心の斜め4マスのうち3マス以上が埋まっていればTスピン
+/// 向いている側の2マスが埋まっていればTスピン、そうでなければTスピンミニになる
+/// ただし、最後の壁蹴り（TST蹴り）で回転した場合はTスピンとして扱う
+fn detect_tspin(board: &Board, pieces: &PieceSet, current: &CurrentBlocks) -> Spin {
+    if !pieces.piece(current.blocktype).spin || current.last_action != BlockAction::Rotate {
+        return Spin::None;
+    }
+
+    // 他の3マス全てと隣接するマスがTブロックの中心になる
+    let cells = current.cells();
+    let center = cells.iter().find(|cell| {
+        cells.iter().filter(|other| (**other - **cell).abs().element_sum() == 1).count() == 3
+    });
+    let Some(center) = center.copied() else {
+        return Spin::None;
+    };
+
+    // 中心から3方向のマスの合計が、Tブロックの向いている方向になる
+    let front = cells.iter().map(|cell| *cell - center).sum::<IVec2>();
+    let side = IVec2::new(front.y, front.x);
+
+    // 斜めのマスが埋まっているか数える（フィールドの外は埋まっているとみなす）
+    let occupied = |offset: IVec2| -> usize {
+        let cell = center + offset;
+        usize::from(!board.is_free(cell.x, cell.y))
+    };
+    let front_count = occupied(front + side) + occupied(front - side);
+    let back_count = occupied(-front + side) + occupied(-front - side);
+
+    if front_count + back_count < 3 {
+        Spin::None
+    } else if front_count == 2 || current.kick == TSPIN_UPGRADE_KICK {
+        Spin::Full
+    } else {
+        Spin::Mini
+    }
+}
+
+/// ブロックの固定後にゲームオーバーになるか判定する関数
+/// ブロックが全て見えているフィールドより上で固定されたらロックアウト、
+/// ライン消去後にバッファゾーンの一番上の行までブロックが積み上がっていればトップアウトになる
+///
+/// # Arguments
+/// * board - ライン消去後の盤面
+/// * cells - 固定したブロックのセル座標
+/// * layout - バッファゾーンの高さを決めるフィールドの配置
+fn topout_reason(board: &Board, cells: &[IVec2], layout: &FieldLayout) -> Option<GameoverReason> {
+    if cells.iter().all(|cell| cell.y < layout.hidden as i32) {
+        return Some(GameoverReason::LockOut);
+    }
+    if board.top_row() == Some(0) {
+        return Some(GameoverReason::TopOut);
+    }
+    None
+}
+
+/// ブロックの固定を管理する関数
+/// `BlockFixed`を受け取り、プレイヤーブロックを固定ブロックに変換し、
+/// ブロックマップから揃った行を削除して、ライン消去イベントを送信します。
+/// 見た目のブロックは消去待ち（`GamePhase::Clearing`）が終わってから詰めます。
+/// ロックアウト、トップアウトした場合はゲームオーバーにします。
+#[allow(clippy::too_many_arguments)]
 pub fn clear_block(
     _fixed: On<BlockFixed>,
     mut commands: Commands,
-    mut player_query: Query<(Entity, &mut Transform), (With<PlayerBlock>, Without<Block>)>,
-    mut block_query: Query<(Entity, &mut Transform), (With<Block>, Without<PlayerBlock>)>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut reason: ResMut<GameoverReason>,
+    mut player_query: Query<(Entity, &PlayerBlock, &mut Transform)>,
     mut blockmap: ResMut<BlockMap>,
-    mut score: ResMut<Score>,
+    mut scorechain: ResMut<ScoreChain>,
+    mut clearingrows: ResMut<ClearingRows>,
+    mut next_phase: ResMut<NextState<GamePhase>>,
+    currentblock: Res<CurrentBlocks>,
+    pieces: Res<BlockPieces>,
+    level: Res<Level>,
+    ruleset: Res<Ruleset>,
 ) {
     info_once!("clear_block");
 
-    // PlayerBlockをBlockに変換
-    for (player_entity, player_transform) in &player_query {
+    // 既に固定されていれば何もしない
+    // （ハードドロップと固定までのタイマーが同じフレームで終わった場合など）
+    if player_query.is_empty() {
+        return;
+    }
+
+    // ブロックを固定する前にTスピンを判定
+    let spin = detect_tspin(&blockmap, &pieces, &currentblock);
+
+    // BlockMapを更新
+    let layout = ruleset.layout();
+    let cells = currentblock.cells();
+    blockmap.insert(&cells, currentblock.blocktype.id());
+
+    // PlayerBlockをBlockに変換（見えなくなるルールでは、見えなくなるまでの時間も追加）
+    for (player_entity, player, mut player_transform) in &mut player_query {
+        let cell = cells[player.0 - 1];
         commands.entity(player_entity).remove::<PlayerBlock>();
-        commands.entity(player_entity).insert(Block);
-
-        // BlockMapを更新
-        blockmap.insert(player_transform.translation.truncate());
-    }
-
-    let map = blockmap.0;
-    for (index, row) in map.iter().enumerate() {
-        // ブロックマップで横1列に1が並んでいたら、その列のブロックを削除する
-        if *row == [1; 10] {
-            // 削除後のブロックマップを更新
-            blockmap.clearline(index);
-
-            // 削除するブロックのy座標を定義
-            let y = FIELD_LEFT_TOP.y + GRID_SIZE * 4.0 - GRID_SIZE * index as f32;
-
-            // プレイヤーブロックをチェックし、削除するY座標と同じなら削除
-            for (player_entity, mut player_transform) in &mut player_query {
-                if player_transform.translation.y == y {
-                    commands.entity(player_entity).despawn();
-                }
-                if player_transform.translation.y > y {
-                    player_transform.translation.y -= GRID_SIZE;
-                }
-            }
-
-            // 固定ブロックをチェックし、削除するY座標と同じなら削除
-            for (block_entity, mut block_transform) in &mut block_query {
-                if block_transform.translation.y == y {
-                    commands.entity(block_entity).despawn();
-                }
-                if block_transform.translation.y > y {
-                    block_transform.translation.y -= GRID_SIZE;
-                }
-            }
-
-            // スコアを更新
-            **score += 1;
+        commands.entity(player_entity).insert(Block(cell));
+        if let Some(lifetime) = BlockLifetime::new(ruleset.stack) {
+            commands.entity(player_entity).insert(lifetime);
         }
+        player_transform.translation = layout.cell_to_translation(cell, BLOCK_DEPTH);
+    }
+
+    // ブロックマップで横1列が埋まっていたら、その列を削除する
+    let rows = blockmap.clear_full_rows();
+
+    // ロックアウト、トップアウトしていればゲームオーバー
+    if let Some(topout) = topout_reason(&blockmap, &cells, &layout) {
+        *reason = topout;
+        next_state.set(AppState::Gameover);
+        return;
+    }
+
+    // ライン消去イベントを送信
+    // 1マスを大きく表示するルールでは、画面上で消えた行数をライン数とする
+    let count = rows.len() * layout.scale;
+    let difficult = count >= 4 || (spin != Spin::None && count > 0);
+    let (b2b, combo) = scorechain.update(count, difficult);
+    let perfect_clear = count > 0 && blockmap.is_empty();
+    commands.trigger(LinesCleared {
+        count,
+        spin,
+        b2b,
+        combo,
+        perfect_clear,
+    });
+
+    // ライン消去後にフィールドが空になっていればパーフェクトクリア
+    // ボーナスの倍率には、ライン消去の点数でレベルが更新される前のレベルを渡す
+    if perfect_clear {
+        commands.trigger(PerfectCleared { count, b2b, level: **level });
+    }
+
+    // 揃った行があれば消去待ちに、なければ次のブロックの出現待ちに移る
+    if rows.is_empty() {
+        next_phase.set(GamePhase::Spawning);
+    } else {
+        **clearingrows = rows;
+        next_phase.set(GamePhase::Clearing);
+    }
+}
+
+/// 消去待ちの行のブロックを非表示にする関数
+pub fn hide_clearing_rows(
+    mut block_query: Query<(&Block, &mut Visibility)>,
+    clearingrows: Res<ClearingRows>,
+) {
+    info_once!("hide_clearing_rows");
+
+    for (block, mut visibility) in &mut block_query {
+        if clearingrows.contains(&(block.0.y as usize)) {
+            *visibility = Visibility::Hidden;
+        }
+    }
+}
+
+/// 消去待ちの行のブロックを削除し、それより上のブロックを下に詰める関数
+pub fn collapse_rows(
+    mut commands: Commands,
+    mut block_query: Query<(Entity, &mut Block, &mut Transform)>,
+    mut clearingrows: ResMut<ClearingRows>,
+    ruleset: Res<Ruleset>,
+) {
+    info_once!("collapse_rows");
+
+    let layout = ruleset.layout();
+    let rows = std::mem::take(&mut **clearingrows);
+
+    // 固定ブロックをチェックし、削除された行なら削除、それ以外は下にずらす
+    for (block_entity, mut block, mut block_transform) in &mut block_query {
+        if rows.contains(&(block.0.y as usize)) {
+            commands.entity(block_entity).despawn();
+            continue;
+        }
+        let count = rows.iter().filter(|row| **row as i32 > block.0.y).count();
+        block.0.y += count as i32;
+        block_transform.translation = layout.cell_to_translation(block.0, BLOCK_DEPTH);
     }
 }
 
 /// ホールドができるかどうか管理する関数
+/// ホールドを使えないルールでは有効にしない
 pub fn enable_hold(
     _fixed: On<BlockFixed>,
     mut holdblocks: ResMut<HoldBlocks>,
+    ruleset: Res<Ruleset>,
 ) {
     info_once!("enable_hold");
 
     // ホールドを有効にする
-    holdblocks.can_hold = true;
+    holdblocks.can_hold = ruleset.hold;
 }
 
-/// ゲームオーバーを管理する関数
-/// 固定されたブロックからゲームオーバーになるかどうかチェックします
-pub fn check_gameover(
-    _fixed: On<BlockFixed>,
-    mut commands: Commands,
-    mut next_state: ResMut<NextState<AppState>>,
-    query: Query<&Transform, With<Block>>,
-) {
-    info_once!("check_gameover");
-
-    let mut is_gameover = false;
-
-    // ゲームオーバーかどうか判定する
-    for transform in &query {
-        let pos = transform.translation;
-        if pos.y >= FIELD_LEFT_TOP.y {
-            if pos.x == FIELD_LEFT_TOP.x + GRID_SIZE * 5.0
-            || pos.x == FIELD_LEFT_TOP.x + GRID_SIZE * 6.0 {
-                is_gameover = true;
-            }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 5x5の盤面の指定したマスを埋めて返す関数
+    fn board(cells: &[(i32, i32)]) -> Board {
+        let mut board = Board::new(5, 5);
+        for (x, y) in cells {
+            board.set(*x, *y, 1);
         }
+        board
     }
 
-    if is_gameover {
-        next_state.set(AppState::Gameover);
-    } else {
-        // ブロックを生成するイベントを送信
-        commands.trigger(BlockSpawned(None));
+    /// 最後に回転したTブロックを返す関数
+    fn rotated_t(pieces: &PieceSet, blockid: usize, pos: IVec2, kick: usize) -> CurrentBlocks {
+        let mut current = CurrentBlocks::new();
+        current.blocktype = pieces.find("T").unwrap();
+        current.blockdata = pieces.shapes(current.blocktype).clone();
+        current.blockid = blockid;
+        current.pos = pos;
+        current.last_action = BlockAction::Rotate;
+        current.kick = kick;
+        current
+    }
+
+    #[test]
+    fn full_tspin_with_both_front_corners() {
+        let pieces = PieceSet::standard();
+        // 下向きのT（中心は(2, 3)）、前の2マスと後ろの1マスが埋まっている
+        let board = board(&[(1, 4), (3, 4), (1, 2)]);
+        let current = rotated_t(&pieces, 2, IVec2::new(1, 2), 0);
+        assert_eq!(detect_tspin(&board, &pieces, &current), Spin::Full);
+    }
+
+    #[test]
+    fn mini_tspin_with_one_front_corner() {
+        let pieces = PieceSet::standard();
+        // 上向きのT（中心は(2, 3)）、前の1マスと後ろの2マスが埋まっている
+        let board = board(&[(1, 2), (1, 4), (3, 4)]);
+        let current = rotated_t(&pieces, 0, IVec2::new(1, 2), 0);
+        assert_eq!(detect_tspin(&board, &pieces, &current), Spin::Mini);
+    }
+
+    #[test]
+    fn upgrade_kick_makes_mini_full() {
+        let pieces = PieceSet::standard();
+        let board = board(&[(1, 2), (1, 4), (3, 4)]);
+        let current = rotated_t(&pieces, 0, IVec2::new(1, 2), TSPIN_UPGRADE_KICK);
+        assert_eq!(detect_tspin(&board, &pieces, &current), Spin::Full);
+    }
+
+    #[test]
+    fn rotate_then_drop_is_not_a_spin() {
+        let pieces = PieceSet::standard();
+        // 右向きのT（中心は(1, 3)）が上から落ちて入れる形
+        let board = board(&[(0, 2), (0, 4), (2, 4)]);
+
+        // その場で回転して入った場合はTスピンミニ
+        let current = rotated_t(&pieces, 1, IVec2::new(0, 2), 0);
+        assert_eq!(detect_tspin(&board, &pieces, &current), Spin::Mini);
+
+        // 上で回転してから落下した場合はTスピンにならない
+        let mut current = rotated_t(&pieces, 1, IVec2::new(0, 0), 0);
+        assert_eq!(current.sonic_drop(&board), 2);
+        assert_eq!(detect_tspin(&board, &pieces, &current), Spin::None);
     }
 }
@@ -1,65 +1,42 @@
 use bevy::prelude::*;
 
-use crate::GRID_SIZE;
 use crate::ingame::utils::prelude::*;
 
+/// ブロックの落下地点を表示するルールか判定する関数
+pub fn ghost_enabled(ruleset: Res<Ruleset>) -> bool {
+    ruleset.ghost
+}
+
 /// ブロックの落下地点を予測し描画する関数
 pub fn draw_gizmos_block(
     mut gizmos: Gizmos,
-    player_query: Query<&Transform, With<PlayerBlock>>,
-    block_query: Query<&Transform, With<Block>>,
+    player_query: Query<&PlayerBlock>,
     current_block: Res<CurrentBlocks>,
+    blockmap: Res<BlockMap>,
+    pieces: Res<BlockPieces>,
+    ruleset: Res<Ruleset>,
 ) {
     info_once!("draw_gizmos_block");
 
-    // 衝突フラグ
-    let mut collision = false;
-    // 現在のステップ数（移動距離）
-    let mut step = 0;
-
-    // フィールドの下限を計算
-    let field_boundary = FIELD_POSITION.y - FIELD_SIZE.y / 2.0;
-
-    // プレイヤーブロックが衝突するか、ステップがフィールド下限に達するまで
-    // プレイヤーブロックの位置の値を下に移動
-    while !collision && step < BLOCK_MAP.len() {
-        for player_transform in &player_query {
-            let player_x = player_transform.translation.x;
-            let player_y = player_transform.translation.y - GRID_SIZE * step as f32;
-
-            for block_transform in &block_query {
-                let block_x = block_transform.translation.x;
-                let block_y = block_transform.translation.y;
-
-                // プレイヤーブロックがブロックに衝突
-                if player_x == block_x && player_y - GRID_SIZE == block_y {
-                    collision = true;
-                }
-            }
-
-            // プレイヤーブロックがフィールドの下限に衝突
-            if player_y - GRID_SIZE < field_boundary {
-                collision = true;
-            }
-        }
-
-        // 衝突しなかったらステップを増加
-        if !collision {
-            step += 1;
-        }
+    // プレイヤーブロックが存在しなければ描画しない
+    if player_query.is_empty() {
+        return;
     }
 
+    // 落下できる距離を計算
+    let cells = current_block.cells();
+    let distance = current_block.drop_distance(&blockmap);
+
     // 衝突した位置にGizmosを描画
-    for player_transform in &player_query {
+    let layout = ruleset.layout();
+    for cell in cells {
         // 描画するGizmosの位置を計算
-        let x = player_transform.translation.x;
-        let y = player_transform.translation.y - GRID_SIZE * step as f32;
-        let translation = Vec2::new(x, y);
+        let translation = layout.cell_to_translation(cell + IVec2::new(0, distance), 0.0).truncate();
 
         // 描画するGizmosの幅と高さを計算
         let margin = 2.0;
-        let width = BLOCK_SIZE - margin;
-        let height = BLOCK_SIZE - margin;
+        let width = layout.block_size() - margin;
+        let height = layout.block_size() - margin;
 
         // 四角形のプリミティブを生成
         let primitive = Rectangle::new(width, height);
@@ -69,7 +46,7 @@ pub fn draw_gizmos_block(
         let isometry = Isometry2d::new(translation, rotation);
 
         // Gizmosの色を取得
-        let color = current_block.blocktype.color();
+        let color = pieces.color(current_block.blocktype);
 
         // Gizmosを使ってブロック落下地点を描画
         gizmos.primitive_2d(&primitive, isometry, color);
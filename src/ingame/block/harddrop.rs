@@ -1,8 +1,8 @@
 use bevy::prelude::*;
 
-use crate::GRID_SIZE;
 use crate::ingame::{
     BlockHarddrop,
+    BlockDropped,
     BlockFixed,
 };
 use crate::ingame::utils::prelude::*;
@@ -12,50 +12,22 @@ use crate::ingame::utils::prelude::*;
 pub fn block_harddrop(
     _was_harddrop: On<BlockHarddrop>,
     mut commands: Commands,
-    mut player_query: Query<&mut Transform, (With<PlayerBlock>, Without<Block>)>,
-    block_query: Query<&Transform, With<Block>>,
+    mut player_query: Query<(&PlayerBlock, &mut Transform)>,
+    mut currentblock: ResMut<CurrentBlocks>,
+    blockmap: Res<BlockMap>,
+    ruleset: Res<Ruleset>,
 ) {
     info_once!("block_harddrop");
 
-    // 衝突フラグ
-    let mut collision = false;
-    // 現在のステップ数（移動距離）
-    let mut step = 0;
-
-    // フィールドの下限を計算
-    let field_boundary = FIELD_POSITION.y - FIELD_SIZE.y / 2.0;
-
-    while !collision && step < BLOCK_MAP.len() {
-        for player_transform in &mut player_query {
-            let player_x = player_transform.translation.x;
-            let player_y = player_transform.translation.y - GRID_SIZE * step as f32;
-
-            for block_transform in &block_query {
-                let block_x = block_transform.translation.x;
-                let block_y = block_transform.translation.y;
-
-                // プレイヤーブロックがブロックに衝突
-                if player_x == block_x && player_y - GRID_SIZE == block_y {
-                    collision = true;
-                }
-            }
-
-            // プレイヤーブロックがフィールドの下限に衝突
-            if player_y - GRID_SIZE < field_boundary {
-                collision = true;
-            }
-        }
-
-        // 衝突しなかったらステップを増加
-        if !collision {
-            step += 1;
-        }
+    // 現在動かしているブロックを接地するまで移動
+    let distance = currentblock.sonic_drop(&blockmap);
+    let layout = ruleset.layout();
+    for (player, mut transform) in &mut player_query {
+        transform.translation = currentblock.position(player.0, &layout);
     }
 
-    // 現在動かしているブロックを移動
-    for mut transform in &mut player_query {
-        transform.translation.y -= GRID_SIZE * step as f32;
-    }
+    // 落下した距離をスコアに反映
+    commands.trigger(BlockDropped { distance: distance as usize, hard: true });
 
     // ブロックを固定
     commands.trigger(BlockFixed);
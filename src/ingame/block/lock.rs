@@ -0,0 +1,39 @@
+use bevy::prelude::*;
+
+use crate::ingame::BlockFixed;
+use crate::ingame::utils::prelude::*;
+
+/// ブロックの固定を管理する関数
+/// ブロックが接地している間だけ`LockDelay`を進め、
+/// タイマーが終わるか、リセット回数が上限に達したらブロックを固定する
+/// リセットしないルール（上限が0）では、タイマーが終わるまで固定しない
+pub fn block_locking(
+    mut commands: Commands,
+    mut lockdelay: ResMut<LockDelay>,
+    player_query: Query<&PlayerBlock>,
+    currentblock: Res<CurrentBlocks>,
+    blockmap: Res<BlockMap>,
+    ruleset: Res<Ruleset>,
+    time: Res<Time>,
+) {
+    info_once!("block_locking");
+
+    // プレイヤーブロックが存在しなければ何もしない
+    if player_query.is_empty() {
+        return;
+    }
+
+    // ブロックが接地していなければタイマーを進めない
+    if currentblock.drop_distance(&blockmap) > 0 {
+        return;
+    }
+
+    // タイマーを進める
+    lockdelay.timer.tick(time.delta());
+
+    // タイマーが終わったか、リセット回数が上限に達したらブロックを固定
+    let exhausted = ruleset.lock_reset_limit > 0 && lockdelay.resets >= ruleset.lock_reset_limit;
+    if lockdelay.timer.is_finished() || exhausted {
+        commands.trigger(BlockFixed);
+    }
+}
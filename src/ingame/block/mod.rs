@@ -1,12 +1,19 @@
 use bevy::prelude::*;
 
-use crate::AppState;
+use crate::Level;
+use crate::ingame::{
+    in_active_phase,
+    GamePhase,
+};
+use crate::ingame::utils::prelude::*;
 
 mod fix;
 mod gizmos;
 mod harddrop;
 mod hold;
+mod lock;
 mod movement;
+mod phase;
 mod rotation;
 mod spawn;
 
@@ -18,15 +25,35 @@ impl Plugin for BlockPlugin {
             .add_observer(spawn::block_spawn)
             .add_observer(rotation::block_rotation)
             .add_observer(movement::block_movement)
+            .add_observer(movement::block_softdrop)
             .add_observer(harddrop::block_harddrop)
             .add_observer(hold::block_hold)
             .add_observer(fix::clear_block)
             .add_observer(fix::enable_hold)
-            .add_observer(fix::check_gameover)
+            .add_systems(OnEnter(GamePhase::Spawning), phase::start_are)
+            .add_systems(OnEnter(GamePhase::Clearing), (
+                phase::start_line_clear,
+                fix::hide_clearing_rows,
+            ))
             .add_systems(Update, (
-                movement::block_falling,
-                gizmos::draw_gizmos_block,
-            ).chain().run_if(in_state(AppState::InGame)))
+                phase::tick_phase_timer,
+                phase::spawn_next.run_if(phase::phase_timer_finished),
+            ).chain().run_if(in_state(GamePhase::Spawning)))
+            .add_systems(Update, (
+                phase::tick_phase_timer,
+                (
+                    fix::collapse_rows,
+                    phase::finish_line_clear,
+                ).run_if(phase::phase_timer_finished),
+            ).chain().run_if(in_state(GamePhase::Clearing)))
+            .add_systems(Update, (
+                movement::update_gravity
+                    .run_if(resource_changed::<Level>.or(resource_changed::<Ruleset>)),
+                movement::block_falling.run_if(in_state(GamePhase::Falling)),
+                phase::update_grounded,
+                lock::block_locking.run_if(in_state(GamePhase::Locking)),
+                gizmos::draw_gizmos_block.run_if(gizmos::ghost_enabled),
+            ).chain().run_if(in_active_phase))
         ;
     }
 }
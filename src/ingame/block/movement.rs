@@ -1,19 +1,40 @@
 use bevy::prelude::*;
 
-use crate::GRID_SIZE;
+use crate::Level;
 use crate::ingame::{
     BlockMoved,
-    BlockFixed,
+    BlockDropped,
+    BlockSoftDropped,
     Direction,
     FallingTimer,
 };
 use crate::ingame::utils::prelude::*;
 
+/// レベルに応じてブロックの落下速度を更新する関数
+/// 重力曲線から1マス落下にかかる秒数を求め、`FallingTimer`に設定する
+pub fn update_gravity(
+    mut timer: ResMut<FallingTimer>,
+    mut gravity: ResMut<Gravity>,
+    level: Res<Level>,
+    ruleset: Res<Ruleset>,
+) {
+    info_once!("update_gravity");
+
+    **gravity = ruleset.gravity.gravity(**level);
+    let interval = ruleset.gravity.interval(**level);
+    timer.set_duration(std::time::Duration::from_secs_f32(interval));
+    debug!("gravity: level={} gravity={:.3}G interval={:.4}s", **level, **gravity, interval);
+}
+
 /// ブロックの落下を管理する関数
 /// `FallingTimer`を使用して一定間隔でブロックを下に移動させる
+/// 1Gを超える重力では、1フレームの間にタイマーが終わった回数だけ下に移動させる
 pub fn block_falling(
     mut commands: Commands,
     mut timer: ResMut<FallingTimer>,
+    player_query: Query<&PlayerBlock>,
+    currentblock: Res<CurrentBlocks>,
+    blockmap: Res<BlockMap>,
     time: Res<Time>,
 ) {
     info_once!("block_falling");
@@ -21,93 +42,82 @@ pub fn block_falling(
     // タイマーを進める
     timer.tick(time.delta());
 
-    // タイマーが終わったかチェック
-    if !timer.just_finished() {
+    // プレイヤーブロックが存在しなければ何もしない
+    if player_query.is_empty() {
         return;
     }
 
+    // 落下する行数を接地するまでの距離に制限する
+    let rows = timer.times_finished_this_tick() as i32;
+    let distance = rows.min(currentblock.drop_distance(&blockmap));
+
     // ブロックを下に移動させるイベントを送信
-    commands.trigger(BlockMoved(Direction::Bottom));
+    for _ in 0..distance {
+        commands.trigger(BlockMoved(Direction::Bottom));
+    }
 }
 
 /// ブロックの移動を管理する関数
-/// `MoveEvent`を受け取り、ブロックの位置を更新し、
-/// 必要に応じてブロックを固定する
+/// `MoveEvent`を受け取り、ブロックの位置を更新する
+/// 接地したブロックの固定は`block_locking`で行う
 pub fn block_movement(
     moved: On<BlockMoved>,
-    mut commands: Commands,
-    mut player_query: Query<&mut Transform, (With<PlayerBlock>, Without<Block>)>,
+    mut player_query: Query<(&PlayerBlock, &mut Transform)>,
     mut currentblock: ResMut<CurrentBlocks>,
-    block_query: Query<&Transform, With<Block>>,
+    mut lockdelay: ResMut<LockDelay>,
+    blockmap: Res<BlockMap>,
+    gravity: Res<Gravity>,
+    ruleset: Res<Ruleset>,
 ) {
     info_once!("block_movement");
 
     let direction = moved.0;
 
-    // フィールドの衝突をチェック
-    for player_transform in &mut player_query {
-        let player_x = player_transform.translation.x;
-        let player_y = player_transform.translation.y;
-
-        match direction {
-            Direction::Left => {
-                if player_x - GRID_SIZE < FIELD_POSITION.x - FIELD_SIZE.x / 2.0 {
-                    return;
-                }
-            }
-            Direction::Right => {
-                if player_x + GRID_SIZE > FIELD_POSITION.x + FIELD_SIZE.x / 2.0 {
-                    return;
-                }
-            }
-            Direction::Bottom => {
-                if player_y - GRID_SIZE < FIELD_POSITION.y - FIELD_SIZE.y / 2.0 {
-                    // ブロックがそこに達した場合、ブロックを固定
-                    commands.trigger(BlockFixed);
-                    return;
-                }
-            }
-        }
-
-        // ブロックの衝突をチェック
-        for block_transform in &block_query {
-            let block_x = block_transform.translation.x;
-            let block_y = block_transform.translation.y;
-
-            match direction {
-                Direction::Left => {
-                    if player_x - GRID_SIZE == block_x && player_y == block_y {
-                        return;
-                    }
-                }
-                Direction::Right => {
-                    if player_x + GRID_SIZE == block_x && player_y == block_y {
-                        return;
-                    }
-                }
-                Direction::Bottom => {
-                    if player_x == block_x && player_y - GRID_SIZE == block_y {
-                        // ブロックが底に達した場合、ブロックを固定
-                        commands.trigger(BlockFixed);
-                        return;
-                    }
-                }
-            }
-        }
+    // 移動先のセル座標を計算
+    let offset = match direction {
+        Direction::Left   => IVec2::new(-1, 0),
+        Direction::Right  => IVec2::new(1, 0),
+        Direction::Bottom => IVec2::new(0, 1),
+    };
+    let cells = currentblock.cells_at(currentblock.blockid, currentblock.pos + offset);
+
+    // フィールドやブロックとの衝突をチェック
+    if !blockmap.fits(&cells) {
+        return;
     }
 
     // 現在のブロック位置を更新
-    match direction {
-        Direction::Left   => currentblock.pos.x -= GRID_SIZE,
-        Direction::Right  => currentblock.pos.x += GRID_SIZE,
-        Direction::Bottom => currentblock.pos.y -= GRID_SIZE,
+    currentblock.pos += offset;
+    currentblock.last_action = BlockAction::Move;
+    // 20Gであれば移動した先で接地させる
+    if gravity.is_instant() {
+        currentblock.sonic_drop(&blockmap);
     }
+    // 固定までのタイマーをリセット
+    lockdelay.reset(currentblock.pos.y, ruleset.lock_reset_limit);
     // ブロックを移動
-    for mut transform in &mut player_query {
-        match direction {
-            Direction::Left   => transform.translation.x -= GRID_SIZE,
-            Direction::Right  => transform.translation.x += GRID_SIZE,
-            Direction::Bottom => transform.translation.y -= GRID_SIZE,
-        }
+    let layout = ruleset.layout();
+    for (player, mut transform) in &mut player_query {
+        transform.translation = currentblock.position(player.0, &layout);
+    }
+}
+
+/// ソフトドロップを管理する関数
+/// `BlockSoftDropped`を受け取り、ブロックが下に移動できる場合のみ
+/// 移動イベントを送信して、落下した距離をスコアに反映する
+pub fn block_softdrop(
+    _softdropped: On<BlockSoftDropped>,
+    mut commands: Commands,
+    currentblock: Res<CurrentBlocks>,
+    blockmap: Res<BlockMap>,
+) {
+    info_once!("block_softdrop");
+
+    // ブロックが接地していれば何もしない
+    if currentblock.drop_distance(&blockmap) == 0 {
+        return;
     }
+
+    commands.trigger(BlockMoved(Direction::Bottom));
+    commands.trigger(BlockDropped { distance: 1, hard: false });
 }
@@ -0,0 +1,117 @@
+use bevy::prelude::*;
+
+use crate::ingame::{
+    GamePhase,
+    BlockSpawned,
+};
+use crate::ingame::utils::prelude::*;
+
+/// 出現待ち（ARE）に入った時にタイマーを初期化する関数
+/// 固定したブロックの高さで出現待ちの時間が変わるルールに対応する
+pub fn start_are(
+    mut timer: ResMut<PhaseTimer>,
+    currentblock: Res<CurrentBlocks>,
+    blockmap: Res<BlockMap>,
+    ruleset: Res<Ruleset>,
+) {
+    info_once!("start_are");
+
+    // 固定したブロックの一番下のセルの、盤面の下から数えた行
+    let bottom = currentblock.cells().iter().map(|cell| cell.y).max().unwrap_or(0);
+    let height = (blockmap.height() as i32 - 1 - bottom).max(0) as usize;
+    *timer = PhaseTimer::new(ruleset.are_time(height));
+}
+
+/// 消去待ちに入った時にタイマーを初期化する関数
+pub fn start_line_clear(
+    mut timer: ResMut<PhaseTimer>,
+    ruleset: Res<Ruleset>,
+) {
+    info_once!("start_line_clear");
+
+    *timer = PhaseTimer::new(ruleset.line_clear_delay);
+}
+
+/// 出現待ちや消去待ちのタイマーを進める関数
+pub fn tick_phase_timer(
+    mut timer: ResMut<PhaseTimer>,
+    time: Res<Time>,
+) {
+    info_once!("tick_phase_timer");
+
+    timer.tick(time.delta());
+}
+
+/// 出現待ちや消去待ちのタイマーが終わったか判定する関数
+pub fn phase_timer_finished(timer: Res<PhaseTimer>) -> bool {
+    timer.is_finished()
+}
+
+/// 出現待ちが終わったら次のブロックを生成して落下中に移る関数
+/// 出現待ちの間にホールドキーが押されていれば、次のブロックをホールドに入れ、
+/// 代わりにホールドしていたブロック（なければその次のブロック）を生成する（IHS）
+/// ブロックアウトの判定が実際に出現するブロックで行われるよう、入れ替えてから生成する
+pub fn spawn_next(
+    mut commands: Commands,
+    mut next_phase: ResMut<NextState<GamePhase>>,
+    mut holdblocks: ResMut<HoldBlocks>,
+    mut nextblocks: ResMut<NextBlocks>,
+    mut blockgenerator: ResMut<BlockGenerator>,
+    initial: Res<InitialActions>,
+) {
+    info_once!("spawn_next");
+
+    next_phase.set(GamePhase::Falling);
+
+    if !(initial.hold && holdblocks.can_hold) {
+        // ブロックを生成するイベントを送信
+        commands.trigger(BlockSpawned(None));
+        return;
+    }
+
+    // 次のブロックを取り出してホールドに入れ、出現するブロックを決める
+    let mut take_next = || {
+        let blocktype = nextblocks[1];
+        *nextblocks = nextblocks.update(blockgenerator.generator.as_mut());
+        blocktype
+    };
+    let next = take_next();
+    let blocktype = match holdblocks.blocktype.replace(next) {
+        Some(blocktype) => blocktype,
+        None => take_next(),
+    };
+    holdblocks.can_hold = false;
+
+    // 決めたブロックを生成するイベントを送信
+    commands.trigger(BlockSpawned(Some(blocktype)));
+}
+
+/// 消去待ちが終わったら出現待ちに移る関数
+pub fn finish_line_clear(mut next_phase: ResMut<NextState<GamePhase>>) {
+    info_once!("finish_line_clear");
+
+    next_phase.set(GamePhase::Spawning);
+}
+
+/// ブロックが接地しているかどうかで、落下中と固定待ちを切り替える関数
+pub fn update_grounded(
+    phase: Res<State<GamePhase>>,
+    mut next_phase: ResMut<NextState<GamePhase>>,
+    player_query: Query<&PlayerBlock>,
+    currentblock: Res<CurrentBlocks>,
+    blockmap: Res<BlockMap>,
+) {
+    info_once!("update_grounded");
+
+    // プレイヤーブロックが存在しなければ何もしない
+    if player_query.is_empty() {
+        return;
+    }
+
+    let grounded = currentblock.drop_distance(&blockmap) == 0;
+    match (phase.get(), grounded) {
+        (GamePhase::Falling, true) => next_phase.set(GamePhase::Locking),
+        (GamePhase::Locking, false) => next_phase.set(GamePhase::Falling),
+        _ => {}
+    }
+}
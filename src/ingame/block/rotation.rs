@@ -1,6 +1,5 @@
 use bevy::prelude::*;
 
-use crate::GRID_SIZE;
 use crate::ingame::{
     BlockRotated,
     Direction,
@@ -9,14 +8,20 @@ use crate::ingame::{
 use crate::ingame::utils::prelude::*;
 
 /// ブロックの回転を管理する関数
-/// `RotationEvent`を受け取り、ブロックの位置を更新し、
-/// 必要に応じてブロックの衝突を処理します
+/// `RotationEvent`を受け取り、リソースに設定された回転ルールに従って
+/// 衝突しない位置にブロックを回転させます
+#[allow(clippy::too_many_arguments)]
 pub fn block_rotation(
     rotated: On<BlockRotated>,
     mut falling_timer: ResMut<FallingTimer>,
-    mut player_query: Query<(&PlayerBlock, &mut Transform), (With<PlayerBlock>, Without<Block>)>,
+    mut player_query: Query<(&PlayerBlock, &mut Transform)>,
     mut currentblock: ResMut<CurrentBlocks>,
-    block_query: Query<&Transform, With<Block>>,
+    mut lockdelay: ResMut<LockDelay>,
+    blockmap: Res<BlockMap>,
+    rotationsystem: Res<BlockRotationSystem>,
+    pieces: Res<BlockPieces>,
+    gravity: Res<Gravity>,
+    ruleset: Res<Ruleset>,
 ) {
     info_once!("block_rotation");
 
@@ -25,72 +30,33 @@ pub fn block_rotation(
     // タイマーをリセット
     falling_timer.reset();
 
-    // 現在のブロックIDを更新
-    currentblock.blockid = match direction {
+    // 回転後のブロックIDを計算
+    let blockid = match direction {
         Direction::Right => (currentblock.blockid + 1) % MAX_BLOCK_COUNT,
         Direction::Left  => (currentblock.blockid + MAX_BLOCK_COUNT - 1) % MAX_BLOCK_COUNT,
         _ => currentblock.blockid,
     };
 
-    // 衝突の回数をカウント
-    let mut count = 0;
-    // X軸の動いた回数
-    let mut step_x = 0;
-    // Y軸の動いた回数
-    let mut step_y = 0;
-    // 衝突をチェック
-    for (player, mut _player_transform) in &mut player_query {
-        while count < MAX_COLLISION_COUNT {
-            // 回転時のブロックの位置を取得
-            let position = currentblock.position(player.0);
-
-            // フィールド左側の衝突判定
-            if position.x < FIELD_POSITION.x - FIELD_SIZE.x / 2.0 {
-                currentblock.pos.x += GRID_SIZE;
-                step_x += 1;
-                count += 1;
-            }
-            // フィールド右側の衝突判定
-            else if position.x > FIELD_POSITION.x + FIELD_SIZE.x / 2.0 {
-                currentblock.pos.x -= GRID_SIZE;
-                step_x -= 1;
-                count += 1;
-            }
-            // フィールド下側の衝突判定
-            else if position.y < FIELD_POSITION.y - FIELD_SIZE.y / 2.0 {
-                currentblock.pos.y += GRID_SIZE;
-                step_y += 1;
-                count += 1;
-            }
-            // ブロック同士の衝突判定
-            else if block_query.iter().any(|block_transform|
-                position == block_transform.translation
-            ) {
-                currentblock.pos.y += GRID_SIZE;
-                step_y += 1;
-                count += 1;
-            }
-            // 衝突がなければループを抜ける
-            else { break; }
-        }
-    }
-
-    // もし衝突判定が規定回数以上あった場合、回転を行わない
-    if count >= MAX_COLLISION_COUNT {
-        // 現在のブロックIDをリセット
-        currentblock.blockid = match direction {
-            Direction::Right => (currentblock.blockid + MAX_BLOCK_COUNT - 1) % MAX_BLOCK_COUNT,
-            Direction::Left  => (currentblock.blockid + 1) % MAX_BLOCK_COUNT,
-            _ => currentblock.blockid,
-        };
-        // 現在のブロック位置をリセット
-        currentblock.pos.x -= GRID_SIZE * step_x as f32;
-        currentblock.pos.y -= GRID_SIZE * step_y as f32;
+    // 回転ルールに従って回転できるか判定し、できなければ回転を行わない
+    let Some(kick) = rotationsystem.rotate(&blockmap, &pieces, &currentblock, blockid) else {
         return;
+    };
+
+    // 現在のブロックIDと位置を更新
+    currentblock.blockid = blockid;
+    currentblock.pos += kick.offset;
+    currentblock.last_action = BlockAction::Rotate;
+    currentblock.kick = kick.index;
+    // 20Gであれば回転した先で接地させる
+    if gravity.is_instant() {
+        currentblock.sonic_drop(&blockmap);
     }
+    // 固定までのタイマーをリセット
+    lockdelay.reset(currentblock.pos.y, ruleset.lock_reset_limit);
 
     // ブロックを回転させる
+    let layout = ruleset.layout();
     for (player, mut player_transform) in &mut player_query {
-        player_transform.translation = currentblock.position(player.0);
+        player_transform.translation = currentblock.position(player.0, &layout);
     }
 }
@@ -1,67 +1,100 @@
 use bevy::prelude::*;
 
-use crate::GRID_SIZE;
-use crate::ingame::BlockSpawned;
+use crate::{
+    AppState,
+    GameoverReason,
+    Level,
+};
+use crate::ingame::{
+    BlockSpawned,
+    Direction,
+};
 use crate::ingame::utils::prelude::*;
 
 /// ブロック生成イベントを処理する関数
 /// `SpawnEvent`を受け取り、新しいブロックを生成してフィールドに配置します
+/// 出現位置が既にあるブロックと重なっていればブロックアウトでゲームオーバーにします
+#[allow(clippy::too_many_arguments)]
 pub fn block_spawn(
     spawned: On<BlockSpawned>,
     mut commands: Commands,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut reason: ResMut<GameoverReason>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
     mut current_block: ResMut<CurrentBlocks>,
+    mut lockdelay: ResMut<LockDelay>,
     mut nextblocks: ResMut<NextBlocks>,
-    mut blockrandomizer: ResMut<BlockRandomizer>,
-    query: Query<&Transform, With<Block>>,
+    mut blockgenerator: ResMut<BlockGenerator>,
+    initial: Res<InitialActions>,
+    blockmap: Res<BlockMap>,
+    rotationsystem: Res<BlockRotationSystem>,
+    pieces: Res<BlockPieces>,
+    gravity: Res<Gravity>,
+    level: Res<Level>,
+    ruleset: Res<Ruleset>,
 ) {
     info_once!("block_spawn");
 
     let blocktype = spawned.0.unwrap_or(nextblocks[1]);
 
-    // 次ブロックデータを更新
-    if spawned.0.is_none() {
-        *nextblocks = nextblocks.update(blockrandomizer.next().unwrap());
+    // 次ブロックデータを更新し、先頭を出現するブロックにする
+    // （ホールドから出したブロックは次ブロックの並びにないため、先頭だけ置き換える）
+    match spawned.0 {
+        Some(blocktype) => nextblocks[0] = blocktype,
+        None => *nextblocks = nextblocks.update(blockgenerator.generator.as_mut()),
     }
 
     // CurrentBlockをリセット
+    let layout = ruleset.layout();
     *current_block = CurrentBlocks::new();
 
     // CurrentBlockのBlockTypeをNextBlockに紐付け
     current_block.blocktype = blocktype;
+    // 回転ルールに対応したブロックの形状を取得
+    current_block.blockdata = rotationsystem.blockdata(&pieces, blocktype);
+    // 出現する向きの形状に合わせて出現位置に配置
+    current_block.pos = layout.spawn_position(&current_block.blockdata[0]) + pieces.piece(blocktype).spawn_offset;
 
-    // PlayerBlockを生成
-    let shape = meshes.add(Rectangle::new(BLOCK_SIZE, BLOCK_SIZE));
-    let mut init_position = BLOCK_POSITION;
-
-    for (index, value) in blocktype.blockdata()[0].iter().enumerate() {
-        // ブロックデータの値が0であればスキップ
-        if *value == 0 {
-            continue;
+    // 出現待ちの間に回転キーが押されていれば、回転した状態で出現させる（IRS）
+    // 回転した状態で重なる場合は回転しない
+    if let Some(direction) = initial.rotation {
+        let blockid = match direction {
+            Direction::Left => MAX_BLOCK_COUNT - 1,
+            _ => 1,
+        };
+        if blockmap.fits(&current_block.cells_at(blockid, current_block.pos)) {
+            current_block.blockid = blockid;
         }
+    }
 
-        // ブロックの位置を計算
-        let mut position = Vec3::new(
-            init_position.x + GRID_SIZE * ((index % 4) as f32),
-            init_position.y - GRID_SIZE * ((index / 4) as f32),
-            init_position.z,
-        );
+    // 出現位置が既にあるブロックと重なっていればゲームオーバー
+    if !blockmap.fits(&current_block.cells()) {
+        *reason = GameoverReason::BlockOut;
+        next_state.set(AppState::Gameover);
+        return;
+    }
 
-        // ブロックが同士が被らないように位置を計算
-        for transform in &query {
-            if position == transform.translation {
-                position.y += GRID_SIZE;
-                init_position.y += GRID_SIZE;
-            }
-        }
+    // 20Gであれば生成した時点で接地させる
+    if gravity.is_instant() {
+        current_block.sonic_drop(&blockmap);
+    }
+
+    // 固定までのタイマーをリセット
+    *lockdelay = LockDelay::new(ruleset.lock_time(**level));
+    lockdelay.lowest = current_block.pos.y;
+
+    // PlayerBlockを生成
+    let shape = meshes.add(Rectangle::new(layout.block_size(), layout.block_size()));
+
+    let color = materials.add(pieces.color(blocktype));
 
-        // PlayerBlockを生成
+    for id in 1..=current_block.cells().len() {
         commands.spawn((
             Mesh2d(shape.clone()),
-            MeshMaterial2d(materials.add(blocktype.color())),
-            Transform::from_xyz(position.x, position.y, position.z),
-            PlayerBlock(*value),
+            MeshMaterial2d(color.clone()),
+            Transform::from_translation(current_block.position(id, &layout)),
+            PlayerBlock(id),
         ));
     }
 }
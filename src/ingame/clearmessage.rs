@@ -0,0 +1,211 @@
+use bevy::prelude::*;
+
+use crate::{
+    PATH_FONT,
+    AppState,
+};
+use super::{
+    LinesCleared,
+    PerfectCleared,
+    Spin,
+};
+use super::utils::prelude::*;
+
+const MESSAGE_DEPTH: f32 = 10.0;
+const MESSAGE_FONT_SIZE: f32 = 10.0;
+const MESSAGE_COLOR: Color = Color::srgb(0.79, 0.83, 0.96);
+const MESSAGE_DURATION: f32 = 1.5;
+
+const BANNER_TEXT: &str = "PERFECT\nCLEAR";
+const BANNER_DEPTH: f32 = 20.0;
+const BANNER_FONT_SIZE: f32 = 24.0;
+const BANNER_COLOR: Color = Color::srgb(1.00, 0.78, 0.47);
+const BANNER_DURATION: f32 = 2.0;
+
+/// ライン消去の種類を表示するテキストのコンポーネント
+#[derive(Component)]
+struct ClearMessage;
+
+/// ライン消去の種類を表示し続ける時間を管理するリソース
+#[derive(Resource, Deref, DerefMut)]
+struct ClearMessageTimer(Timer);
+
+/// パーフェクトクリアを知らせるバナーのコンポーネント
+#[derive(Component)]
+struct PerfectClearBanner;
+
+/// パーフェクトクリアのバナーを表示し続ける時間を管理するリソース
+#[derive(Resource, Deref, DerefMut)]
+struct PerfectClearTimer(Timer);
+
+/// ライン消去の種類を表すテキストを生成する関数
+/// Tスピン、消去したライン数、B2B、コンボを1行ずつ並べる
+fn message(cleared: &LinesCleared) -> String {
+    let mut lines = Vec::new();
+
+    match cleared.spin {
+        Spin::None => {}
+        Spin::Mini => lines.push("T-SPIN MINI".to_string()),
+        Spin::Full => lines.push("T-SPIN".to_string()),
+    }
+    match cleared.count {
+        0 => {}
+        1 => lines.push("SINGLE".to_string()),
+        2 => lines.push("DOUBLE".to_string()),
+        3 => lines.push("TRIPLE".to_string()),
+        _ => lines.push("TETRIS".to_string()),
+    }
+    if cleared.b2b {
+        lines.push("BACK-TO-BACK".to_string());
+    }
+    if cleared.combo > 0 {
+        lines.push(format!("{} COMBO", cleared.combo));
+    }
+
+    lines.join("\n")
+}
+
+/// ライン消去の種類を表示するテキストを生成する関数
+/// フィールドの左側の列の中央に配置する
+fn setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    ruleset: Res<Ruleset>,
+) {
+    info_once!("setup");
+
+    let layout = ruleset.layout();
+    let position = Vec3::new(
+        layout.left() - SIDE_BOARD_WIDTH / 2.0,
+        FIELD_POSITION.y,
+        MESSAGE_DEPTH,
+    );
+
+    let font = asset_server.load(PATH_FONT);
+    commands.spawn((
+        Text2d::new(""),
+        TextFont {
+            font: font.clone(),
+            font_size: MESSAGE_FONT_SIZE,
+            ..Default::default()
+        },
+        TextColor(MESSAGE_COLOR),
+        TextLayout::new_with_justify(Justify::Center),
+        Transform::from_translation(position),
+        ClearMessage,
+    ));
+
+    // パーフェクトクリアのバナーはフィールドの中央に重ねて表示する
+    commands.spawn((
+        Text2d::new(""),
+        TextFont {
+            font,
+            font_size: BANNER_FONT_SIZE,
+            ..Default::default()
+        },
+        TextColor(BANNER_COLOR),
+        TextLayout::new_with_justify(Justify::Center),
+        Transform::from_translation(FIELD_POSITION.with_z(BANNER_DEPTH)),
+        ClearMessage,
+        PerfectClearBanner,
+    ));
+}
+
+/// ライン消去時にテキストを更新する関数
+/// 表示する内容がなければテキストを更新しない
+fn update(
+    cleared: On<LinesCleared>,
+    mut query: Query<&mut Text2d, (With<ClearMessage>, Without<PerfectClearBanner>)>,
+    mut timer: ResMut<ClearMessageTimer>,
+) {
+    info_once!("update");
+
+    let text = message(&cleared);
+    if text.is_empty() {
+        return;
+    }
+
+    for mut span in &mut query {
+        **span = text.clone();
+    }
+    timer.reset();
+}
+
+/// パーフェクトクリア時にバナーを表示する関数
+fn show_banner(
+    _cleared: On<PerfectCleared>,
+    mut query: Query<&mut Text2d, With<PerfectClearBanner>>,
+    mut timer: ResMut<PerfectClearTimer>,
+) {
+    info_once!("show_banner");
+
+    for mut span in &mut query {
+        **span = BANNER_TEXT.to_string();
+    }
+    timer.reset();
+}
+
+/// 一定時間が経ったらバナーを消す関数
+fn hide_banner(
+    mut query: Query<&mut Text2d, With<PerfectClearBanner>>,
+    mut timer: ResMut<PerfectClearTimer>,
+    time: Res<Time>,
+) {
+    info_once!("hide_banner");
+
+    timer.tick(time.delta());
+    if !timer.just_finished() {
+        return;
+    }
+
+    for mut span in &mut query {
+        span.clear();
+    }
+}
+
+/// 一定時間が経ったらテキストを消す関数
+fn hide(
+    mut query: Query<&mut Text2d, (With<ClearMessage>, Without<PerfectClearBanner>)>,
+    mut timer: ResMut<ClearMessageTimer>,
+    time: Res<Time>,
+) {
+    info_once!("hide");
+
+    timer.tick(time.delta());
+    if !timer.just_finished() {
+        return;
+    }
+
+    for mut span in &mut query {
+        span.clear();
+    }
+}
+
+/// ライン消去の種類を表示するテキストを削除する関数
+fn despawn(
+    mut commands: Commands,
+    query: Query<Entity, With<ClearMessage>>,
+) {
+    for entity in &query {
+        commands.entity(entity).despawn();
+    }
+}
+
+pub struct ClearMessagePlugin;
+
+impl Plugin for ClearMessagePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(ClearMessageTimer(Timer::from_seconds(MESSAGE_DURATION, TimerMode::Once)))
+            .insert_resource(PerfectClearTimer(Timer::from_seconds(BANNER_DURATION, TimerMode::Once)))
+            .add_systems(OnEnter(AppState::InGame), setup)
+            .add_observer(update)
+            .add_observer(show_banner)
+            .add_systems(Update, (
+                hide,
+                hide_banner,
+            ).run_if(in_state(AppState::InGame)))
+            .add_systems(OnExit(AppState::Gameover), despawn)
+        ;
+    }
+}
@@ -1,23 +1,44 @@
 use bevy::prelude::*;
 
-use crate::AppState;
+use crate::{
+    GRID_SIZE,
+    WINDOW_SIZE,
+    AppState,
+};
 use super::utils::prelude::*;
 
 const FIELD_COLOR: Color = Color::srgb(0.13, 0.14, 0.21);
+/// 画面の端とフィールドやボードの間の余白
+const SCREEN_MARGIN: f32 = GRID_SIZE * 2.0;
 
 #[derive(Component)]
 struct Field;
 
 /// フィールドのセットアップを行う関数
+/// フィールドの大きさはルールから決まり、画面に収まらない場合はカメラを引いて表示する
 fn setup(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
+    mut projection_query: Query<&mut Projection, With<Camera2d>>,
+    ruleset: Res<Ruleset>,
 ) {
     info_once!("setup");
 
+    let layout = ruleset.layout();
+
+    // フィールドとボードが画面に収まるようにカメラの拡大率を設定
+    let content = layout.content_size() + SCREEN_MARGIN * 2.0;
+    let scale = (content / WINDOW_SIZE).max_element().max(1.0);
+    for mut projection in &mut projection_query {
+        if let Projection::Orthographic(orthographic) = projection.as_mut() {
+            orthographic.scale = scale;
+        }
+    }
+
     // フィールドを作成
-    let shape = meshes.add(Rectangle::new(FIELD_SIZE.x, FIELD_SIZE.y));
+    let size = layout.size();
+    let shape = meshes.add(Rectangle::new(size.x, size.y));
     commands.spawn((
         Mesh2d(shape),
         MeshMaterial2d(materials.add(FIELD_COLOR)),
@@ -30,6 +51,7 @@ fn setup(
 /// ステートがゲームオーバーから抜けた時に実行されます
 fn despawn(
     mut commands: Commands,
+    mut projection_query: Query<&mut Projection, With<Camera2d>>,
     query: Query<Entity, With<Field>>,
 ) {
     info_once!("despawn");
@@ -37,6 +59,13 @@ fn despawn(
     for entity in &query {
         commands.entity(entity).despawn();
     }
+
+    // カメラの拡大率を元に戻す
+    for mut projection in &mut projection_query {
+        if let Projection::Orthographic(orthographic) = projection.as_mut() {
+            orthographic.scale = 1.0;
+        }
+    }
 }
 
 pub struct FieldPlugin;
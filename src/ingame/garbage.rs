@@ -0,0 +1,206 @@
+use bevy::prelude::*;
+
+use crate::{
+    AppState,
+    GameoverReason,
+};
+use super::{
+    GamePhase,
+    GarbageRaised,
+    LinesCleared,
+};
+use super::utils::prelude::*;
+
+const METER_WIDTH: f32 = 4.0;
+const METER_DEPTH: f32 = 5.0;
+const METER_COLOR: Color = Color::srgb(1.00, 0.46, 0.50);
+
+/// せり上がりを待っているおじゃまブロックの行数を表示するメーターのコンポーネント
+#[derive(Component)]
+struct GarbageMeter;
+
+/// ブロックがラインを消去せずに固定された時に、待っているおじゃまブロックをせり上げる関数
+fn raise_on_lock(
+    cleared: On<LinesCleared>,
+    mut commands: Commands,
+    garbage: Res<GarbageQueue>,
+) {
+    info_once!("raise_on_lock");
+
+    // ラインを消去した場合はせり上がらない
+    if cleared.count == 0 && !garbage.pending.is_empty() {
+        commands.trigger(GarbageRaised);
+    }
+}
+
+/// ライン消去後に次のブロックの出現待ちに入った時に、待っているおじゃまブロックをせり上げる関数
+/// 時間でせり上がるルールでは、ラインを消去してもせり上がりを止められない
+fn raise_on_spawning(
+    mut commands: Commands,
+    garbage: Res<GarbageQueue>,
+) {
+    info_once!("raise_on_spawning");
+
+    if !garbage.pending.is_empty() {
+        commands.trigger(GarbageRaised);
+    }
+}
+
+/// 時間でせり上がるおじゃまブロックのタイマーを進める関数
+/// タイマーが終わるたびに1行をせり上がりの待ちに追加し、次の間隔を短くする
+fn tick_garbage_timer(
+    mut timer: ResMut<GarbageTimer>,
+    mut garbage: ResMut<GarbageQueue>,
+    time: Res<Time>,
+) {
+    info_once!("tick_garbage_timer");
+
+    timer.tick(time.delta());
+    if timer.just_finished() {
+        garbage.push(1);
+        timer.accelerate();
+        debug!("garbage timer: next={:.2}s", timer.duration().as_secs_f32());
+    }
+}
+
+/// 待っているおじゃまブロックをせり上げる関数
+/// 盤面と固定ブロックを上にずらし、空いた下の行におじゃまブロックを生成する
+/// 盤面の一番上までブロックが押し上げられた場合はトップアウトでゲームオーバーにします
+#[allow(clippy::too_many_arguments)]
+fn raise_garbage(
+    _raised: On<GarbageRaised>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut reason: ResMut<GameoverReason>,
+    mut block_query: Query<(Entity, &mut Block, &mut Transform)>,
+    mut blockmap: ResMut<BlockMap>,
+    mut garbage: ResMut<GarbageQueue>,
+    ruleset: Res<Ruleset>,
+) {
+    info_once!("raise_garbage");
+
+    if garbage.pending.is_empty() {
+        return;
+    }
+
+    // 盤面の下からおじゃまブロックを追加
+    let layout = ruleset.layout();
+    let rows = garbage.take_rows(layout.width, ruleset.garbage);
+    let overflow = blockmap.push_rows(&rows);
+
+    // 固定ブロックを追加した行数だけ上にずらし、盤面からはみ出たものは削除する
+    let count = rows.len() as i32;
+    for (block_entity, mut block, mut block_transform) in &mut block_query {
+        block.0.y -= count;
+        if block.0.y < 0 {
+            commands.entity(block_entity).despawn();
+            continue;
+        }
+        block_transform.translation = layout.cell_to_translation(block.0, BLOCK_DEPTH);
+    }
+
+    // 追加した行のおじゃまブロックを生成
+    let shape = meshes.add(Rectangle::new(layout.block_size(), layout.block_size()));
+    let color = materials.add(GARBAGE_COLOR);
+    let height = blockmap.height() as i32;
+    for (y, row) in (0..height).rev().zip(rows.iter().rev()) {
+        for (x, value) in row.iter().enumerate() {
+            if *value == 0 {
+                continue;
+            }
+            let cell = IVec2::new(x as i32, y);
+            commands.spawn((
+                Mesh2d(shape.clone()),
+                MeshMaterial2d(color.clone()),
+                Transform::from_translation(layout.cell_to_translation(cell, BLOCK_DEPTH)),
+                Block(cell),
+            ));
+        }
+    }
+
+    // 盤面の一番上まで押し上げられていればゲームオーバー
+    if overflow || blockmap.top_row() == Some(0) {
+        *reason = GameoverReason::TopOut;
+        next_state.set(AppState::Gameover);
+    }
+}
+
+/// おじゃまブロックのメーターを生成する関数
+/// フィールドの左側に、フィールドの下端から伸びるように配置する
+/// 時間でせり上がるルールでは、せり上がりのタイマーも最初の間隔で初期化する
+fn setup(
+    mut commands: Commands,
+    mut timer: ResMut<GarbageTimer>,
+    ruleset: Res<Ruleset>,
+) {
+    info_once!("setup");
+
+    if let Some(interval) = ruleset.garbage_interval {
+        *timer = GarbageTimer::new(interval);
+    }
+
+    let layout = ruleset.layout();
+    commands.spawn((
+        Sprite::from_color(METER_COLOR, Vec2::new(METER_WIDTH, 0.0)),
+        Transform::from_xyz(layout.left() - METER_WIDTH / 2.0, layout.bottom(), METER_DEPTH),
+        GarbageMeter,
+    ));
+}
+
+/// せり上がりを待っている行数に合わせてメーターの長さを更新する関数
+/// メーターの長さはフィールドの高さを超えない
+fn update_meter(
+    mut query: Query<(&mut Sprite, &mut Transform), With<GarbageMeter>>,
+    garbage: Res<GarbageQueue>,
+    ruleset: Res<Ruleset>,
+) {
+    info_once!("update_meter");
+
+    let layout = ruleset.layout();
+    let height = (garbage.total().min(layout.height) as f32) * layout.cell_size();
+    for (mut sprite, mut transform) in &mut query {
+        sprite.custom_size = Some(Vec2::new(METER_WIDTH, height));
+        transform.translation.y = layout.bottom() + height / 2.0;
+    }
+}
+
+/// おじゃまブロックが時間でせり上がるルールか判定する関数
+fn has_garbage_timer(ruleset: Res<Ruleset>) -> bool {
+    ruleset.garbage_interval.is_some()
+}
+
+/// おじゃまブロックのメーターを削除する関数
+fn despawn(
+    mut commands: Commands,
+    query: Query<Entity, With<GarbageMeter>>,
+) {
+    info_once!("despawn");
+
+    for entity in &query {
+        commands.entity(entity).despawn();
+    }
+}
+
+pub struct GarbagePlugin;
+
+impl Plugin for GarbagePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(OnEnter(AppState::InGame), setup)
+            .add_observer(raise_on_lock)
+            .add_observer(raise_garbage)
+            .add_systems(OnEnter(GamePhase::Spawning), raise_on_spawning.run_if(has_garbage_timer))
+            .add_systems(Update, tick_garbage_timer
+                .run_if(in_state(AppState::InGame))
+                .run_if(has_garbage_timer)
+            )
+            .add_systems(Update, update_meter
+                .run_if(in_state(AppState::InGame))
+                .run_if(resource_changed::<GarbageQueue>)
+            )
+            .add_systems(OnExit(AppState::Gameover), despawn)
+        ;
+    }
+}
@@ -0,0 +1,187 @@
+use bevy::prelude::*;
+
+use crate::{
+    AppState,
+    Level,
+};
+use crate::gamemode::{
+    GameMode,
+    Grade,
+    PlayTime,
+    Ranking,
+    SectionTimes,
+};
+use super::{
+    in_active_phase,
+    LinesCleared,
+};
+use super::utils::prelude::*;
+
+/// 内部段位が上がるまでに貯める段位ポイント
+const GRADE_POINTS_PER_GRADE: usize = 100;
+/// レベルによる段位ポイントの倍率が上がるまでのレベル数
+const GRADE_LEVEL_DIVISOR: usize = 250;
+
+/// 内部段位ごとの段位ポイントが1減るまでのフレーム数と、
+/// 1〜4ラインを消去した時に得られる段位ポイント（TGM2準拠、コンボの倍率は省略）
+const GRADE_TABLE: [(usize, [usize; 4]); 32] = [
+    (125, [10, 20, 40, 50]),
+    (80, [10, 20, 30, 40]),
+    (80, [10, 20, 30, 40]),
+    (50, [10, 15, 30, 40]),
+    (45, [10, 15, 20, 40]),
+    (45, [5, 15, 20, 30]),
+    (45, [5, 10, 20, 30]),
+    (40, [5, 10, 15, 30]),
+    (40, [5, 10, 15, 30]),
+    (40, [5, 10, 15, 30]),
+    (40, [2, 12, 13, 30]),
+    (40, [2, 12, 13, 30]),
+    (30, [2, 12, 13, 30]),
+    (30, [2, 12, 13, 30]),
+    (30, [2, 12, 13, 30]),
+    (20, [2, 12, 13, 30]),
+    (20, [2, 12, 13, 30]),
+    (20, [2, 12, 13, 30]),
+    (20, [2, 12, 13, 30]),
+    (20, [2, 12, 13, 30]),
+    (15, [2, 12, 13, 30]),
+    (15, [2, 12, 13, 30]),
+    (15, [2, 12, 13, 30]),
+    (15, [2, 12, 13, 30]),
+    (15, [2, 12, 13, 30]),
+    (15, [2, 12, 13, 30]),
+    (15, [2, 12, 13, 30]),
+    (15, [2, 12, 13, 30]),
+    (15, [2, 12, 13, 30]),
+    (15, [2, 12, 13, 30]),
+    (10, [2, 12, 13, 30]),
+    (10, [2, 12, 13, 30]),
+];
+
+/// GMになるための条件（レベル, 必要な内部段位, 制限時間（秒））（TGM準拠）
+/// レベル300で段位1、レベル500でS4、レベル999でS9以上に達している必要がある
+const GM_REQUIREMENTS: [(usize, usize, f32); 3] = [
+    (300, 12, 255.0),
+    (500, 20, 450.0),
+    (TGM_MAX_LEVEL, 31, 810.0),
+];
+
+/// ゲーム開始時に段位とセクションタイムをリセットする関数
+fn setup(
+    mut grade: ResMut<Grade>,
+    mut sections: ResMut<SectionTimes>,
+) {
+    info_once!("setup");
+
+    *grade = Grade::default();
+    sections.clear();
+}
+
+/// ライン消去時に段位ポイントを加算する関数
+/// 消去したライン数と内部段位から得られるポイントに、レベルによる倍率を掛ける
+/// 段位ポイントが貯まったら内部段位を上げる
+fn add_grade_points(
+    cleared: On<LinesCleared>,
+    mut grade: ResMut<Grade>,
+    level: Res<Level>,
+    mode: Res<GameMode>,
+) {
+    info_once!("add_grade_points");
+
+    if mode.ranking() != Ranking::Grade || cleared.count == 0 || grade.is_max() {
+        return;
+    }
+
+    let (_, points) = GRADE_TABLE[grade.internal];
+    let multiplier = 1 + **level / GRADE_LEVEL_DIVISOR;
+    grade.points += points[cleared.count.min(points.len()) - 1] * multiplier;
+    grade.decay = 0.0;
+
+    if grade.points >= GRADE_POINTS_PER_GRADE {
+        grade.internal += 1;
+        grade.points = 0;
+        debug!("grade up: {}", grade.name());
+    }
+}
+
+/// 段位ポイントを時間経過で減らす関数
+/// ブロックを操作している間だけ減り、コンボ中は減らない
+fn decay_grade_points(
+    mut grade: ResMut<Grade>,
+    scorechain: Res<ScoreChain>,
+    time: Res<Time>,
+) {
+    info_once!("decay_grade_points");
+
+    if scorechain.combo.is_some() || grade.points == 0 {
+        return;
+    }
+
+    let (frames, _) = GRADE_TABLE[grade.internal.min(GRADE_TABLE.len() - 1)];
+    let interval = frames as f32 / FRAMES_PER_SECOND;
+    grade.decay += time.delta_secs();
+    if grade.decay >= interval {
+        grade.decay -= interval;
+        grade.points -= 1;
+    }
+}
+
+/// セクションの区切りを越えた時にセクションタイムを記録する関数
+/// GMの条件を確認し、満たしていなければGMになれなくする
+fn record_section_time(
+    mut grade: ResMut<Grade>,
+    mut sections: ResMut<SectionTimes>,
+    playtime: Res<PlayTime>,
+    level: Res<Level>,
+    mode: Res<GameMode>,
+) {
+    info_once!("record_section_time");
+
+    if mode.ranking() != Ranking::Grade {
+        return;
+    }
+
+    // レベルの上限に達したら最後のセクションも終わりにする
+    let completed = if **level >= TGM_MAX_LEVEL {
+        TGM_MAX_LEVEL.div_ceil(TGM_SECTION_LEVELS)
+    } else {
+        **level / TGM_SECTION_LEVELS
+    };
+    let elapsed = playtime.elapsed_secs();
+    while sections.len() < completed {
+        let split = elapsed - sections.iter().sum::<f32>();
+        debug!("section {}: {:.2}s", sections.len(), split);
+        sections.push(split);
+    }
+
+    // GMの条件を確認する（条件のレベルに達した時のタイムはセクションタイムの合計から求める）
+    for (requirement, internal, limit) in GM_REQUIREMENTS {
+        let count = requirement.div_ceil(TGM_SECTION_LEVELS);
+        if sections.len() < count || grade.gm || grade.gm_failed {
+            continue;
+        }
+        let reached = sections[..count].iter().sum::<f32>();
+        if grade.internal < internal || reached > limit {
+            debug!("gm failed at level {}", requirement);
+            grade.gm_failed = true;
+        } else if requirement == TGM_MAX_LEVEL {
+            grade.gm = true;
+        }
+    }
+}
+
+pub struct GradingPlugin;
+
+impl Plugin for GradingPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(OnEnter(AppState::InGame), setup)
+            .add_observer(add_grade_points)
+            .add_systems(Update, (
+                decay_grade_points.run_if(in_active_phase),
+                record_section_time.run_if(resource_changed::<Level>),
+            ).run_if(in_state(AppState::InGame)))
+        ;
+    }
+}
@@ -5,36 +5,41 @@ use crate::{
     PATH_FONT,
     AppState,
 };
-use super::BlockHolded;
 use super::utils::prelude::*;
 
-const BOARD_SIZE: Vec2 = Vec2::new(
-    GRID_SIZE_HALF * 6.0,
+pub const BOARD_SIZE: Vec2 = Vec2::new(
+    SIDE_BOARD_WIDTH,
     GRID_SIZE_HALF * 9.0,
 );
-const BOARD_POSITION: Vec3 = Vec3::new(
-    FIELD_POSITION.x - FIELD_SIZE.x / 2.0 - BOARD_SIZE.x / 2.0,
-    FIELD_POSITION.y + FIELD_SIZE.y / 2.0 - BOARD_SIZE.y / 2.0,
-    0.0,
-);
 const BOARD_COLOR: Color = Color::srgb(0.16, 0.18, 0.26);
 
 const HOLD_TEXT: &str = "HOLD";
 const HOLD_FONT_SIZE: f32 = 20.0;
-const HOLD_POSITION: Vec3 = Vec3::new(
-    BOARD_POSITION.x,
-    BOARD_POSITION.y + BOARD_SIZE.y / 2.0 - HOLD_FONT_SIZE / 2.0 - HOLD_PADDING,
+/// ボードの中心からテキストまでの相対位置
+const HOLD_OFFSET: Vec3 = Vec3::new(
+    0.0,
+    BOARD_SIZE.y / 2.0 - HOLD_FONT_SIZE / 2.0 - HOLD_PADDING,
     10.0, 
 );
 const HOLD_PADDING: f32 = GRID_SIZE_HALF * 1.75;
 
 const BLOCK_SIZE: Vec2 = Vec2::new(GRID_SIZE_HALF, GRID_SIZE_HALF);
-const BLOCK_INIT_POSITION: Vec3 = Vec3::new(
-    BOARD_POSITION.x - BOARD_SIZE.x / 2.0 + BLOCK_SIZE.x / 2.0,
-    BOARD_POSITION.y + BOARD_SIZE.y / 2.0 - BLOCK_SIZE.y / 2.0 - GRID_SIZE_HALF * 4.0,
+/// ボードの中心からブロックの描画を始める位置までの相対位置
+const BLOCK_INIT_OFFSET: Vec3 = Vec3::new(
+    -BOARD_SIZE.x / 2.0 + BLOCK_SIZE.x / 2.0,
+    BOARD_SIZE.y / 2.0 - BLOCK_SIZE.y / 2.0 - GRID_SIZE_HALF * 4.0,
     10.0,
 );
 
+/// フィールドの左上に合わせたボードの中心の位置を返す関数
+pub fn board_position(layout: &FieldLayout) -> Vec3 {
+    Vec3::new(
+        layout.left() - BOARD_SIZE.x / 2.0,
+        layout.top() - BOARD_SIZE.y / 2.0,
+        0.0,
+    )
+}
+
 /// ホールドしたブロックを画面上に描画するコンポーネント
 #[derive(Component)]
 struct HoldBoard;
@@ -49,18 +54,27 @@ struct HoldBlock {
 /// ホールドされたブロックを描画する関数
 /// フィールド左上に配置し、初めは空の状態で描画する
 /// その後ホールドされたら、そのブロックを表示する
+/// ホールドを使えないルールでは何も描画しない
 fn setup(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
     asset_server: Res<AssetServer>,
+    pieces: Res<BlockPieces>,
+    ruleset: Res<Ruleset>,
 ) {
     info_once!("setup");
 
+    if !ruleset.hold {
+        return;
+    }
+
+    let board_position = board_position(&ruleset.layout());
+
     // ボードを生成する
     commands.spawn((
         Sprite::from_color(BOARD_COLOR, BOARD_SIZE),
-        Transform::from_translation(BOARD_POSITION),
+        Transform::from_translation(board_position),
         HoldBoard,
     ));
 
@@ -73,14 +87,14 @@ fn setup(
             font_size: HOLD_FONT_SIZE,
             ..Default::default()
         },
-        Transform::from_translation(HOLD_POSITION),
+        Transform::from_translation(board_position + HOLD_OFFSET),
         HoldBoard,
     ));
 
-    // 空のブロックを生成する
+    // 空のブロックを生成する（一番大きいブロックのセルの数だけ用意する）
     let shape = meshes.add(Rectangle::new(BLOCK_SIZE.x, BLOCK_SIZE.y));
     let color = Color::NONE;
-    for block_id in 1..=BLOCK_UNIT_COUNT {
+    for block_id in 1..=pieces.max_units() {
         commands.spawn((
             Mesh2d(shape.clone()),
             MeshMaterial2d(materials.add(color)),
@@ -91,41 +105,50 @@ fn setup(
 }
 
 /// ホールドしたブロックを更新する関数
-/// ブロックの状態をゲーム進行に合わせて更新を行う
+/// ホールドの状態が変わるたびに、ホールドしているブロックの表示を更新する
 fn update(
-    holded: On<BlockHolded>,
     mut holdblock_query: Query<(
         &mut Transform,
         &mut MeshMaterial2d<ColorMaterial>,
+        &mut Visibility,
         &mut HoldBlock
     ), With<HoldBlock>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
+    holdblocks: Res<HoldBlocks>,
+    pieces: Res<BlockPieces>,
+    ruleset: Res<Ruleset>,
 ) {
     info_once!("update");
 
-    let blocktype = holded.0;
+    let Some(blocktype) = holdblocks.blocktype else {
+        return;
+    };
+    let board_position = board_position(&ruleset.layout());
+
+    let shape = &pieces.shapes(blocktype)[0];
 
-    for (mut transform, mut color, mut holdblock) in &mut holdblock_query.iter_mut() {
+    for (mut transform, mut color, mut visibility, mut holdblock) in &mut holdblock_query.iter_mut() {
         // 現在のホールドブロックIDが形状データに含まれるか検索
-        if let Some((index, _)) = &blocktype.blockdata()[0]
-            .iter()
-            .enumerate()
-            .find(|(_, &v)| v == holdblock.block_id)
-        {
-            // ブロックの色を更新
-            *color = MeshMaterial2d(materials.add(blocktype.color()));
-            // ブロックタイプを更新
-            holdblock.blocktype = Some(blocktype);
-
-            // ブロック表示位置を計算（タイプごとに微調整）
-            let pos = blocktype.calculate_position(BLOCK_INIT_POSITION);
-            // ブロックの座標を設定
-            transform.translation = Vec3::new(
-                pos.x + GRID_SIZE_HALF * ((index % 4) as f32),
-                pos.y - GRID_SIZE_HALF * ((index / 4) as f32),
-                10.0,
-            );
-        }
+        let Some(cell) = shape.get(holdblock.block_id - 1) else {
+            // セルの数が少ないブロックでは、余ったエンティティを隠す
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+
+        // ブロックの色を更新
+        *color = MeshMaterial2d(materials.add(pieces.color(blocktype)));
+        *visibility = Visibility::Inherited;
+        // ブロックタイプを更新
+        holdblock.blocktype = Some(blocktype);
+
+        // ブロック表示位置を計算（タイプごとに微調整）
+        let pos = pieces.preview_position(blocktype, board_position + BLOCK_INIT_OFFSET);
+        // ブロックの座標を設定
+        transform.translation = Vec3::new(
+            pos.x + GRID_SIZE_HALF * cell.x as f32,
+            pos.y - GRID_SIZE_HALF * cell.y as f32,
+            10.0,
+        );
     }
 }
 
@@ -145,7 +168,10 @@ impl Plugin for HoldBlockPlugin {
     fn build(&self, app: &mut App) {
         app
             .add_systems(OnEnter(AppState::InGame), setup)
-            .add_observer(update)
+            .add_systems(Update, update
+                .run_if(in_state(AppState::InGame))
+                .run_if(resource_changed::<HoldBlocks>)
+            )
             .add_systems(OnExit(AppState::Gameover), despawn)
         ;
     }
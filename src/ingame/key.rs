@@ -1,11 +1,19 @@
-use bevy::prelude::*;
+use bevy::{
+    prelude::*,
+    time::Stopwatch,
+};
+use std::time::Duration;
 
 use crate::AppState;
+
 use super::{
+    in_active_phase,
+    GamePhase,
     BlockMoved,
     BlockRotated,
     BlockHarddrop,
     BlockHolded,
+    BlockSoftDropped,
     Direction,
 };
 use super::utils::prelude::*;
@@ -18,15 +26,55 @@ const KEY_BLOCK_ROTATION_RIGHT: KeyCode = KeyCode::ArrowUp;
 const KEY_BLOCK_HARDDROP: KeyCode = KeyCode::Space;
 const KEY_BLOCK_HOLD: KeyCode = KeyCode::KeyC;
 
+/// キーを押し続けた時に、このフレームでブロックを移動させる回数を返す関数
+/// 押し始めてから`delay`秒を超えたら`interval`秒ごとに移動させる
+/// `interval`が0なら上限の回数だけ移動させる
+/// 押し続けた時間までに移動する回数の合計から、前のフレームまでの回数を引いて求める
+///
+/// # Arguments
+/// * stopwatch - キーを押し続けている時間を計るストップウォッチ（このフレームの分も進めたもの）
+/// * delta - このフレームで進めた秒数
+/// * delay - 連続移動が始まるまでの秒数
+/// * interval - 連続移動の間隔の秒数
+/// * max - 1フレームで移動させる回数の上限
+fn repeat_count(stopwatch: &Stopwatch, delta: f32, delay: f32, interval: f32, max: usize) -> usize {
+    let elapsed = stopwatch.elapsed_secs();
+    if elapsed <= delay {
+        return 0;
+    }
+    // 間隔が0なら上限まで移動させる
+    if interval <= 0.0 {
+        return max;
+    }
+    let total = |elapsed: f32| -> usize {
+        if elapsed <= delay { 0 } else { 1 + ((elapsed - delay) / interval) as usize }
+    };
+    (total(elapsed) - total((elapsed - delta).max(0.0))).min(max)
+}
+
+/// 回転した時に左右の連続移動を`das_cut`秒止める関数
+fn das_cut(stopwatch: &mut Stopwatch, handling: &Handling) {
+    if handling.das_cut <= 0.0 {
+        return;
+    }
+    let elapsed = stopwatch.elapsed_secs().min(handling.das) - handling.das_cut;
+    stopwatch.set_elapsed(Duration::from_secs_f32(elapsed.max(0.0)));
+}
+
 /// ブロック左移動キーが入力された時の挙動を決める関数
 fn key_block_moveleft(
     mut commands: Commands,
     mut moveleft_timer: ResMut<MoveLeftTimer>,
     keyboard_input: Res<ButtonInput<KeyCode>>,
+    handling: Res<Handling>,
+    ruleset: Res<Ruleset>,
+    blockmap: Res<BlockMap>,
     time: Res<Time>,
 ) {
     info_once!("key_block_moveleft");
 
+    let handling = ruleset.effective_handling(&handling);
+
     // ブロック左移動キー入力時
     if keyboard_input.just_pressed(KEY_BLOCK_MOVE_LEFT) {
         // ブロック左移動イベントを発火
@@ -37,18 +85,12 @@ fn key_block_moveleft(
     if keyboard_input.pressed(KEY_BLOCK_MOVE_LEFT) {
         // ブロック左移動タイマーを進める
         moveleft_timer.0.tick(time.delta());
-        // ブロック左移動タイマーが切れたら、タイマーをリセットし、イベントを発火
-        if moveleft_timer.0.elapsed_secs() > BLOCK_MOVE_SPEED {
-            moveleft_timer.0.reset();
+        // DASを超えたらARRの間隔でイベントを発火
+        let count = repeat_count(&moveleft_timer.0, time.delta_secs(), handling.das, handling.arr, blockmap.width());
+        for _ in 0..count {
             commands.trigger(BlockMoved(Direction::Left));
         }
     }
-
-    // ブロック左移動キーを離した時
-    if keyboard_input.just_released(KEY_BLOCK_MOVE_LEFT) {
-        // ブロック左移動タイマーをリセット
-        moveleft_timer.0.reset();
-    }
 }
 
 /// ブロック右移動キーが入力された時の挙動を決める関数
@@ -56,10 +98,15 @@ fn key_block_moveright(
     mut commands: Commands,
     mut moveright_timer: ResMut<MoveRightTimer>,
     keyboard_input: Res<ButtonInput<KeyCode>>,
+    handling: Res<Handling>,
+    ruleset: Res<Ruleset>,
+    blockmap: Res<BlockMap>,
     time: Res<Time>,
 ) {
     info_once!("key_block_moveright");
 
+    let handling = ruleset.effective_handling(&handling);
+
     // ブロック右移動キー入力時
     if keyboard_input.just_pressed(KEY_BLOCK_MOVE_RIGHT) {
         // ブロック右移動イベントを発火
@@ -70,34 +117,38 @@ fn key_block_moveright(
     if keyboard_input.pressed(KEY_BLOCK_MOVE_RIGHT) {
         // ブロック右移動タイマーを進める
         moveright_timer.0.tick(time.delta());
-        // ブロック右移動タイマーが切れたら、タイマーをリセットし、イベントを発火
-        if moveright_timer.0.elapsed_secs() > BLOCK_MOVE_SPEED {
-            moveright_timer.0.reset();
+        // DASを超えたらARRの間隔でイベントを発火
+        let count = repeat_count(&moveright_timer.0, time.delta_secs(), handling.das, handling.arr, blockmap.width());
+        for _ in 0..count {
             commands.trigger(BlockMoved(Direction::Right));
         }
     }
-
-    // ブロック右移動キーを離した時
-    if keyboard_input.just_released(KEY_BLOCK_MOVE_RIGHT) {
-        // ブロック右移動タイマーをリセット
-        moveright_timer.0.reset();
-    }
 }
 
 /// ブロック下移動キーが入力された時の挙動を決める関数
+/// 落下速度をソフトドロップの倍率で割った間隔でブロックを下に移動させる
+#[allow(clippy::too_many_arguments)]
 fn key_block_movebottom(
     mut commands: Commands,
     mut falling_timer: ResMut<FallingTimer>,
     mut movebottom_timer: ResMut<MoveBottomTimer>,
     keyboard_input: Res<ButtonInput<KeyCode>>,
+    handling: Res<Handling>,
+    ruleset: Res<Ruleset>,
+    blockmap: Res<BlockMap>,
     time: Res<Time>,
 ) {
     info_once!("key_block_movebottom");
 
+    let handling = ruleset.effective_handling(&handling);
+
+    // ソフトドロップ時の移動間隔を計算
+    let interval = falling_timer.duration().as_secs_f32() / handling.soft_drop_factor;
+
     // ブロック下移動キー入力時
     if keyboard_input.just_pressed(KEY_BLOCK_MOVE_BOTTOM) {
-        // ブロック下移動イベントを発火
-        commands.trigger(BlockMoved(Direction::Bottom));
+        // ソフトドロップイベントを発火
+        commands.trigger(BlockSoftDropped);
         // ブロック落下タイマーを一時停止し、タイマーをリセット
         falling_timer.0.pause();
         falling_timer.0.reset();
@@ -107,16 +158,37 @@ fn key_block_movebottom(
     if keyboard_input.pressed(KEY_BLOCK_MOVE_BOTTOM) {
         // ブロック下移動タイマーを進める
         movebottom_timer.0.tick(time.delta());
-        // ブロック下移動タイマーが切れたら、タイマーをリセットし、イベントを発火
-        if movebottom_timer.0.elapsed_secs() > BLOCK_MOVE_SPEED {
-            movebottom_timer.0.reset();
-            commands.trigger(BlockMoved(Direction::Bottom));
+        // 移動間隔ごとにイベントを発火（間隔が0なら接地するまで移動）
+        let count = repeat_count(&movebottom_timer.0, time.delta_secs(), interval, interval, blockmap.height());
+        for _ in 0..count {
+            commands.trigger(BlockSoftDropped);
         }
     }
+}
 
-    // ブロック下移動キー離した時
+/// 移動キーを離した時に、連続移動のタイマーを戻す関数
+/// 出現待ちやライン消去中に離した場合も拾えるよう、ゲーム中は常に実行する
+fn key_block_release(
+    mut falling_timer: ResMut<FallingTimer>,
+    mut moveleft_timer: ResMut<MoveLeftTimer>,
+    mut moveright_timer: ResMut<MoveRightTimer>,
+    mut movebottom_timer: ResMut<MoveBottomTimer>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+) {
+    info_once!("key_block_release");
+
+    // ブロック左移動キーを離した時、ブロック左移動タイマーをリセット
+    if keyboard_input.just_released(KEY_BLOCK_MOVE_LEFT) {
+        moveleft_timer.0.reset();
+    }
+
+    // ブロック右移動キーを離した時、ブロック右移動タイマーをリセット
+    if keyboard_input.just_released(KEY_BLOCK_MOVE_RIGHT) {
+        moveright_timer.0.reset();
+    }
+
+    // ブロック下移動キーを離した時、ブロック下移動タイマーをリセットし、落下タイマーの一時停止を解除
     if keyboard_input.just_released(KEY_BLOCK_MOVE_BOTTOM) {
-        // ブロック下移動タイマーをリセットし、一時停止を解除
         movebottom_timer.0.reset();
         falling_timer.0.unpause();
     }
@@ -125,38 +197,56 @@ fn key_block_movebottom(
 /// ブロック左回転キーが入力された時の挙動を決める関数
 fn key_block_rotateleft(
     mut commands: Commands,
+    mut moveleft_timer: ResMut<MoveLeftTimer>,
+    mut moveright_timer: ResMut<MoveRightTimer>,
     keyboard_input: Res<ButtonInput<KeyCode>>,
+    handling: Res<Handling>,
+    ruleset: Res<Ruleset>,
 ) {
     info_once!("key_block_rotationleft");
 
-    // ブロック左回転キーが押されたら、イベントを発火
+    let handling = ruleset.effective_handling(&handling);
+
+    // ブロック左回転キーが押されたら、イベントを発火し、左右の連続移動を止める
     if keyboard_input.just_pressed(KEY_BLOCK_ROTATION_LEFT) {
         commands.trigger(BlockRotated(Direction::Left));
+        das_cut(&mut moveleft_timer.0, handling);
+        das_cut(&mut moveright_timer.0, handling);
     }
 }
 
 /// ブロック右回転キーが入力された時の挙動を決める関数
 fn key_block_rotateright(
     mut commands: Commands,
+    mut moveleft_timer: ResMut<MoveLeftTimer>,
+    mut moveright_timer: ResMut<MoveRightTimer>,
     keyboard_input: Res<ButtonInput<KeyCode>>,
+    handling: Res<Handling>,
+    ruleset: Res<Ruleset>,
 ) {
     info_once!("key_block_rotationright");
 
-    // ブロック右回転キーが押されたら、イベントを発火
+    let handling = ruleset.effective_handling(&handling);
+
+    // ブロック右回転キーが押されたら、イベントを発火し、左右の連続移動を止める
     if keyboard_input.just_pressed(KEY_BLOCK_ROTATION_RIGHT) {
         commands.trigger(BlockRotated(Direction::Right));
+        das_cut(&mut moveleft_timer.0, handling);
+        das_cut(&mut moveright_timer.0, handling);
     }
 }
 
 /// ハードドロップキーが入力された時の挙動を決める関数
+/// ハードドロップを使えないルールでは何もしない
 fn key_block_harddrop(
     mut commands: Commands,
     keyboard_input: Res<ButtonInput<KeyCode>>,
+    ruleset: Res<Ruleset>,
 ) {
     info_once!("key_block_harddrop");
 
     // ハードドロップキーが押されたら、イベントを発火
-    if keyboard_input.just_pressed(KEY_BLOCK_HARDDROP) {
+    if ruleset.hard_drop && keyboard_input.just_pressed(KEY_BLOCK_HARDDROP) {
         commands.trigger(BlockHarddrop);
     }
 }
@@ -180,6 +270,34 @@ fn key_block_hold(
     }
 }
 
+/// 出現待ちの間に押されている回転キー、ホールドキーを記録する関数
+/// ルールでIRS、IHSが無効になっている場合は記録しない
+fn key_initial_actions(
+    mut initial: ResMut<InitialActions>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    ruleset: Res<Ruleset>,
+) {
+    info_once!("key_initial_actions");
+
+    initial.rotation = if !ruleset.irs {
+        None
+    } else if keyboard_input.pressed(KEY_BLOCK_ROTATION_RIGHT) {
+        Some(Direction::Right)
+    } else if keyboard_input.pressed(KEY_BLOCK_ROTATION_LEFT) {
+        Some(Direction::Left)
+    } else {
+        None
+    };
+    initial.hold = ruleset.ihs && keyboard_input.pressed(KEY_BLOCK_HOLD);
+}
+
+/// 出現待ちが終わった時に記録したキーを消す関数
+fn reset_initial_actions(mut initial: ResMut<InitialActions>) {
+    info_once!("reset_initial_actions");
+
+    *initial = InitialActions::default();
+}
+
 pub struct KeyPlugin;
 
 impl Plugin for KeyPlugin {
@@ -193,7 +311,10 @@ impl Plugin for KeyPlugin {
                 key_block_rotateright,
                 key_block_harddrop,
                 key_block_hold,
-            ).run_if(in_state(AppState::InGame)))
+            ).run_if(in_active_phase))
+            .add_systems(Update, key_block_release.run_if(in_state(AppState::InGame)))
+            .add_systems(Update, key_initial_actions.run_if(in_state(GamePhase::Spawning)))
+            .add_systems(OnExit(GamePhase::Spawning), reset_initial_actions)
         ;
     }
 }
@@ -1,14 +1,46 @@
 use bevy::prelude::*;
 
+use crate::AppState;
 use crate::ingame::utils::prelude::*;
 
 mod block;
+mod clearmessage;
 mod field;
+mod garbage;
+mod grading;
 mod key;
+mod mode;
 mod nextblock;
 mod holdblock;
 mod utils;
 mod scoreboard;
+mod scoring;
+mod stack;
+
+/// ゲーム中の進行状況を表すサブステート
+/// - Spawning: ブロックが固定されてから次のブロックが出現するまで（ARE）
+/// - Falling: ブロックが落下している間
+/// - Locking: ブロックが接地して固定されるのを待っている間
+/// - Clearing: ラインが揃ってから消去されるまで
+///
+/// ゲーム開始時のブロックは`setup`で生成されるため、落下中から始める
+#[derive(SubStates, Default, Debug, Clone, PartialEq, Eq, Hash)]
+#[source(AppState = AppState::InGame)]
+enum GamePhase {
+    Spawning,
+    #[default]
+    Falling,
+    Locking,
+    Clearing,
+}
+
+/// ブロックの移動や回転を行えるフェーズか判定する関数
+fn in_active_phase(phase: Option<Res<State<GamePhase>>>) -> bool {
+    matches!(
+        phase.as_deref().map(State::get),
+        Some(GamePhase::Falling | GamePhase::Locking),
+    )
+}
 
 /// ブロック移動イベント（左右下移動）
 #[derive(Event)]
@@ -32,6 +64,52 @@ struct BlockSpawned(Option<BlockType>);
 #[derive(Event, Default)]
 struct BlockFixed;
 
+/// ブロック落下イベント（ソフトドロップ、ハードドロップ）
+/// 落下した距離に応じてスコアが加算される
+#[derive(Event)]
+struct BlockDropped {
+    distance: usize,
+    hard: bool,
+}
+
+/// ソフトドロップイベント
+/// ブロックが下に移動できる場合のみ移動させる
+#[derive(Event, Default)]
+struct BlockSoftDropped;
+
+/// ライン消去イベント
+/// ブロックが固定されるたびに、消去したライン数が0でも送信される
+/// - count: 消去したライン数
+/// - spin: Tスピンの種類
+/// - b2b: 直前の難しい消去から続けて難しい消去をしたか
+/// - combo: 連続でラインを消去した回数（初回は0）
+/// - perfect_clear: 消去後にフィールドが空になったか
+#[derive(Event, Clone, Copy, Debug)]
+struct LinesCleared {
+    count: usize,
+    spin: Spin,
+    b2b: bool,
+    combo: usize,
+    perfect_clear: bool,
+}
+
+/// パーフェクトクリアイベント
+/// ライン消去後にフィールドが空になった時に、`LinesCleared`の後に送信される
+/// - count: 消去したライン数
+/// - b2b: 直前の難しい消去から続けて難しい消去をしたか
+/// - level: 消去する前のレベル
+#[derive(Event, Clone, Copy, Debug)]
+struct PerfectCleared {
+    count: usize,
+    b2b: bool,
+    level: usize,
+}
+
+/// おじゃまブロックのせり上がりイベント
+/// せり上がりを待っているおじゃまブロックを全て盤面の下から追加する
+#[derive(Event, Default)]
+struct GarbageRaised;
+
 /// ブロックホールドイベント
 #[derive(Event)]
 struct BlockHolded(BlockType);
@@ -44,11 +122,21 @@ enum Direction {
     Bottom,
 }
 
+/// Tスピンの種類
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+enum Spin {
+    #[default]
+    None,
+    Mini,
+    Full,
+}
+
 pub struct IngamePlugin;
 
 impl Plugin for IngamePlugin {
     fn build(&self, app: &mut App) {
         app
+            .add_sub_state::<GamePhase>()
             .add_plugins(field::FieldPlugin)
             .add_plugins(key::KeyPlugin)
             .add_plugins(block::BlockPlugin)
@@ -56,6 +144,12 @@ impl Plugin for IngamePlugin {
             .add_plugins(holdblock::HoldBlockPlugin)
             .add_plugins(utils::UtilsPlugin)
             .add_plugins(scoreboard::ScoreboardPlugin)
+            .add_plugins(scoring::ScoringPlugin)
+            .add_plugins(clearmessage::ClearMessagePlugin)
+            .add_plugins(garbage::GarbagePlugin)
+            .add_plugins(mode::ModePlugin)
+            .add_plugins(grading::GradingPlugin)
+            .add_plugins(stack::StackPlugin)
         ;
     }
 }
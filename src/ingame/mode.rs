@@ -0,0 +1,222 @@
+use bevy::prelude::*;
+
+use crate::{
+    AppState,
+    ClearedLines,
+    Level,
+    GameoverReason,
+};
+use crate::gamemode::{
+    GameMode,
+    Goal,
+    ModeOptions,
+    PlayTime,
+};
+use super::utils::prelude::*;
+
+/// レベルが上がらないルールで使用する、レベルが上がるまでのライン数
+const NO_LEVEL_UP: usize = usize::MAX;
+/// ディグレースで盤面の下に並べるおじゃまブロックの行数
+const DIG_RACE_GARBAGE: usize = 10;
+/// サバイバルでおじゃまブロックが時間でせり上がる最初の間隔（秒）
+const SURVIVAL_GARBAGE_INTERVAL: f32 = 6.0;
+/// クラシックBで選択した高さごとに並べるおじゃまブロックの行数
+const CLASSIC_GARBAGE_ROWS: [usize; 6] = [0, 3, 5, 8, 10, 12];
+/// クラシックで左右の連続移動が始まるまでのフレーム数と、連続移動の間隔のフレーム数（NES版テトリス準拠）
+const NES_DAS_FRAMES: f32 = 16.0;
+const NES_ARR_FRAMES: f32 = 6.0;
+/// クラシックの出現待ちと消去待ちのフレーム数（NES版テトリス準拠、出現待ちは下の2行に固定した時の値）
+const NES_ARE_FRAMES: f32 = 10.0;
+const NES_LINE_CLEAR_FRAMES: f32 = 20.0;
+/// フェードで固定したブロックが見えなくなり始めるまでの秒数
+const FADING_STACK_DELAY: f32 = 5.0;
+/// ビッグで盤面の1マスを画面上で何マス四方に表示するか
+const BIG_BLOCK_SCALE: usize = 2;
+
+/// NES版テトリスに合わせたルールを返す関数
+/// ホールド、ハードドロップ、落下地点の表示がなく、次のブロックは1つだけ表示する
+/// 接地したブロックは次に落下するタイミングで固定され、移動や回転で固定を遅らせられない
+/// 操作感と出現待ち、消去待ちの時間もNES版テトリスのフレーム数に合わせ、出現待ちは固定した高さで長くなる
+fn classic_ruleset(options: &ModeOptions) -> Ruleset {
+    Ruleset {
+        lock_reset_limit: 0,
+        lock_on_gravity: true,
+        are: NES_ARE_FRAMES / FRAMES_PER_SECOND,
+        are_by_height: true,
+        line_clear_delay: NES_LINE_CLEAR_FRAMES / FRAMES_PER_SECOND,
+        gravity: GravityCurve::Nes,
+        start_level: options.level,
+        level_progress: LevelProgress::Nes,
+        hold: false,
+        hard_drop: false,
+        handling: Some(Handling {
+            das: NES_DAS_FRAMES / FRAMES_PER_SECOND,
+            arr: NES_ARR_FRAMES / FRAMES_PER_SECOND,
+            das_cut: 0.0,
+            ..Default::default()
+        }),
+        previews: 1,
+        ghost: false,
+        scoring: ScoringKind::Nes,
+        irs: false,
+        ihs: false,
+        ..Default::default()
+    }
+}
+
+/// ゲームモードに合わせたルールを返す関数
+///
+/// # Arguments
+/// * mode - 遊ぶゲームモード
+/// * options - メインメニューで選択された設定
+fn mode_ruleset(mode: GameMode, options: &ModeOptions) -> Ruleset {
+    match mode {
+        GameMode::Marathon => Ruleset::default(),
+        GameMode::Fading => Ruleset {
+            stack: StackVisibility::Fading(FADING_STACK_DELAY),
+            ..Default::default()
+        },
+        GameMode::Invisible => Ruleset {
+            stack: StackVisibility::Invisible,
+            ..Default::default()
+        },
+        GameMode::Sprint | GameMode::Ultra => Ruleset {
+            lines_per_level: NO_LEVEL_UP,
+            ..Default::default()
+        },
+        GameMode::DigRace => Ruleset {
+            lines_per_level: NO_LEVEL_UP,
+            garbage: GarbageStyle::Messy,
+            start_garbage: DIG_RACE_GARBAGE,
+            ..Default::default()
+        },
+        GameMode::Survival => Ruleset {
+            lines_per_level: NO_LEVEL_UP,
+            garbage: GarbageStyle::Messy,
+            garbage_interval: Some(SURVIVAL_GARBAGE_INTERVAL),
+            ..Default::default()
+        },
+        GameMode::ClassicA => classic_ruleset(options),
+        // Bタイプはレベルが上がらず、開始時のおじゃまブロックはマスごとにばらばらに空く
+        GameMode::ClassicB => Ruleset {
+            level_progress: LevelProgress::Lines,
+            lines_per_level: NO_LEVEL_UP,
+            garbage: GarbageStyle::Scattered,
+            start_garbage: CLASSIC_GARBAGE_ROWS[options.height.min(CLASSIC_GARBAGE_ROWS.len() - 1)],
+            ..classic_ruleset(options)
+        },
+        // TGMと同じく、固定までの時間は1段下がった時だけリセットし、スコアは計算せずに段位で競う
+        GameMode::Master => Ruleset {
+            lock_reset_limit: 0,
+            gravity: GravityCurve::Tgm,
+            start_level: 0,
+            level_progress: LevelProgress::Tgm,
+            scoring: ScoringKind::None,
+            ..Default::default()
+        },
+        // 盤面を縦横半分にして大きく表示するため、同じ大きさのフィールドで2列ずつ動き、2行ずつ消える
+        GameMode::Big => Ruleset {
+            width: BOARD_WIDTH / BIG_BLOCK_SCALE,
+            visible_height: BOARD_VISIBLE_HEIGHT / BIG_BLOCK_SCALE,
+            buffer_height: BUFFER_HEIGHT / BIG_BLOCK_SCALE,
+            block_scale: BIG_BLOCK_SCALE,
+            ..mode_ruleset(GameMode::Master, options)
+        },
+    }
+}
+
+/// ゲームモードに合わせたランダマイザの種類を返す関数
+/// マスターとクラシック以外では、メインメニューで選択されたランダマイザを使う
+fn mode_generator(mode: GameMode, options: &ModeOptions) -> GeneratorKind {
+    match mode {
+        GameMode::Master | GameMode::Big => GeneratorKind::Tgm3,
+        GameMode::ClassicA | GameMode::ClassicB => GeneratorKind::Nes,
+        _ => GeneratorKind::ALL[options.randomizer],
+    }
+}
+
+/// ゲームモードに合わせた回転ルールの種類を返す関数
+fn mode_rotation(mode: GameMode) -> RotationKind {
+    match mode {
+        GameMode::Master | GameMode::Big => RotationKind::Ars,
+        GameMode::ClassicA | GameMode::ClassicB => RotationKind::Nes,
+        _ => RotationKind::Srs,
+    }
+}
+
+/// 選択されたゲームモードをルールに反映する関数
+/// ゲーム開始時の各セットアップより先にルールを決めるため、メインメニューを抜ける時に実行される
+fn apply_mode(
+    mut ruleset: ResMut<Ruleset>,
+    mut blockgenerator: ResMut<BlockGenerator>,
+    mut rotationsystem: ResMut<BlockRotationSystem>,
+    mode: Res<GameMode>,
+    options: Res<ModeOptions>,
+) {
+    info_once!("apply_mode");
+
+    debug!("game mode: {:?}", *mode);
+    *ruleset = mode_ruleset(*mode, &options);
+    // ランダマイザはゲーム開始時にシード値から作り直されるため、種類だけ差し替える
+    blockgenerator.kind = mode_generator(*mode, &options);
+    *rotationsystem = BlockRotationSystem::new(mode_rotation(*mode));
+}
+
+/// ゲーム開始時に遊んでいる時間をリセットする関数
+fn setup(mut playtime: ResMut<PlayTime>) {
+    info_once!("setup");
+
+    playtime.reset();
+}
+
+/// 遊んでいる時間を進める関数
+fn tick_play_time(
+    mut playtime: ResMut<PlayTime>,
+    time: Res<Time>,
+) {
+    info_once!("tick_play_time");
+
+    playtime.tick(time.delta());
+}
+
+/// ゲームモードのクリア条件を満たしたか判定する関数
+/// 満たしていればクリアとしてゲームを終了する
+fn check_goal(
+    mut next_state: ResMut<NextState<AppState>>,
+    mut reason: ResMut<GameoverReason>,
+    playtime: Res<PlayTime>,
+    lines: Res<ClearedLines>,
+    level: Res<Level>,
+    blockmap: Res<BlockMap>,
+    mode: Res<GameMode>,
+) {
+    info_once!("check_goal");
+
+    let finished = match mode.goal() {
+        Goal::Lines(goal) => (**lines >= goal).then_some(GameoverReason::Cleared),
+        Goal::Time(limit) => (playtime.elapsed_secs() >= limit).then_some(GameoverReason::TimeUp),
+        Goal::Level(goal) => (**level >= goal).then_some(GameoverReason::Cleared),
+        Goal::Garbage => (blockmap.count_rows_with(GARBAGE_ID) == 0).then_some(GameoverReason::Cleared),
+        Goal::Survive => None,
+    };
+
+    if let Some(finished) = finished {
+        *reason = finished;
+        next_state.set(AppState::Gameover);
+    }
+}
+
+pub struct ModePlugin;
+
+impl Plugin for ModePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(OnExit(AppState::Mainmenu), apply_mode)
+            .add_systems(OnEnter(AppState::InGame), setup)
+            .add_systems(Update, (
+                tick_play_time,
+                check_goal,
+            ).chain().run_if(in_state(AppState::InGame)))
+        ;
+    }
+}
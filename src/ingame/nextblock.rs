@@ -5,39 +5,41 @@ use crate::{
     PATH_FONT,
     AppState,
 };
-use super::{
-    FIELD_SIZE,
-    FIELD_POSITION,
-    BlockSpawned,
-};
+use super::BlockSpawned;
 use super::utils::prelude::*;
 
-const BOARD_SIZE: Vec2 = Vec2::new(
-    GRID_SIZE_HALF * 6.0,
+pub const BOARD_SIZE: Vec2 = Vec2::new(
+    SIDE_BOARD_WIDTH,
     GRID_SIZE_HALF * 20.0,
 );
-const BOARD_POSITION: Vec3 = Vec3::new(
-    FIELD_POSITION.x + FIELD_SIZE.x / 2.0 + BOARD_SIZE.x / 2.0,
-    FIELD_POSITION.y + FIELD_SIZE.y / 2.0 - BOARD_SIZE.y / 2.0,
-    0.0,
-);
 const BOARD_COLOR: Color = Color::srgb(0.16, 0.18, 0.26);
 
 const NEXT_TEXT: &str = "NEXT";
 const NEXT_FONT_SIZE: f32 = 20.0;
-const NEXT_POSITION: Vec3 = Vec3::new(
-    BOARD_POSITION.x, 
-    BOARD_POSITION.y + BOARD_SIZE.y / 2.0 - NEXT_FONT_SIZE / 2.0 - GRID_SIZE_HALF * 1.75,
+/// ボードの中心からテキストまでの相対位置
+const NEXT_OFFSET: Vec3 = Vec3::new(
+    0.0,
+    BOARD_SIZE.y / 2.0 - NEXT_FONT_SIZE / 2.0 - GRID_SIZE_HALF * 1.75,
     10.0, 
 );
 
 const BLOCK_SIZE: Vec2 = Vec2::new(GRID_SIZE_HALF, GRID_SIZE_HALF);
-const BLOCK_INIT_POSITION: Vec3 = Vec3::new(
-    BOARD_POSITION.x - BOARD_SIZE.x / 2.0 + BLOCK_SIZE.x / 2.0,
-    BOARD_POSITION.y + BOARD_SIZE.y / 2.0 - BLOCK_SIZE.y / 2.0 - GRID_SIZE_HALF * 5.0,
+/// ボードの中心からブロックの描画を始める位置までの相対位置
+const BLOCK_INIT_OFFSET: Vec3 = Vec3::new(
+    -BOARD_SIZE.x / 2.0 + BLOCK_SIZE.x / 2.0,
+    BOARD_SIZE.y / 2.0 - BLOCK_SIZE.y / 2.0 - GRID_SIZE_HALF * 5.0,
     10.0,
 );
 
+/// フィールドの右上に合わせたボードの中心の位置を返す関数
+pub fn board_position(layout: &FieldLayout) -> Vec3 {
+    Vec3::new(
+        layout.right() + BOARD_SIZE.x / 2.0,
+        layout.top() - BOARD_SIZE.y / 2.0,
+        0.0,
+    )
+}
+
 #[derive(Component)]
 pub struct NextBoard;
 
@@ -51,19 +53,23 @@ struct NextBlock {
 
 /// 次にくるブロックを描画する関数
 /// フィールド右上に配置し、次回に生成される
-/// ブロックの形を3つ表示する
+/// ブロックの形をルールで決められた数だけ表示する
 fn setup(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
     asset_server: Res<AssetServer>,
+    pieces: Res<BlockPieces>,
+    ruleset: Res<Ruleset>,
 ) {
     info_once!("setup");
 
+    let board_position = board_position(&ruleset.layout());
+
     // ボードを生成する
     commands.spawn((
         Sprite::from_color(BOARD_COLOR, BOARD_SIZE),
-        Transform::from_translation(BOARD_POSITION),
+        Transform::from_translation(board_position),
         NextBoard,
     ));
 
@@ -76,16 +82,16 @@ fn setup(
             font_size: NEXT_FONT_SIZE,
             ..Default::default()
         },
-        Transform::from_translation(NEXT_POSITION),
+        Transform::from_translation(board_position + NEXT_OFFSET),
         NextBoard,
     ));
 
-    // 空の次ブロックを生成する
+    // 空の次ブロックを生成する（一番大きいブロックのセルの数だけ用意する）
     let shape = meshes.add(Rectangle::new(BLOCK_SIZE.x, BLOCK_SIZE.y));
     let color = Color::NONE;
-    let blocktype = BlockType::TypeI;
-    for nextblock_id in 1..=NEXT_BLOCK_COUNT - 1 {
-        for block_id in 1..=BLOCK_UNIT_COUNT {
+    let blocktype = BlockType::default();
+    for nextblock_id in 1..=ruleset.previews.min(NEXT_BLOCK_COUNT - 1) {
+        for block_id in 1..=pieces.max_units() {
             commands.spawn((
                 Mesh2d(shape.clone()),
                 MeshMaterial2d(materials.add(color)),
@@ -103,42 +109,48 @@ fn update(
     mut query: Query<(
         &mut Transform,
         &mut MeshMaterial2d<ColorMaterial>,
+        &mut Visibility,
         &mut NextBlock
     ), With<NextBlock>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
     nextblocks: Res<NextBlocks>,
+    pieces: Res<BlockPieces>,
+    ruleset: Res<Ruleset>,
 ) {
     info_once!("update");
 
+    let block_init_position = board_position(&ruleset.layout()) + BLOCK_INIT_OFFSET;
+
     // 次ブロック一覧をループ
-    for (mut transform, mut color, mut nextblock) in &mut query {
+    for (mut transform, mut color, mut visibility, mut nextblock) in &mut query {
         let nextblock_id = nextblock.nextblock_id;
         let block_id = nextblock.block_id;
         let blocktype = nextblocks[nextblock_id];
 
         // ブロックの色を更新
-        *color = MeshMaterial2d(materials.add(blocktype.color()));
+        *color = MeshMaterial2d(materials.add(pieces.color(blocktype)));
         // ブロックの形を更新
         nextblock.blocktype = blocktype;
 
-        // 現在のブロックデータ配列内に該当するindexを検索
-        if let Some((index, _)) = blocktype.blockdata()[0]
-            .iter()
-            .enumerate()
-            .find(|(_, &blockdata_value)| blockdata_value == block_id)
-        {
-            // ブロックの描画y座標を計算
-            let y = BLOCK_INIT_POSITION.y - GRID_SIZE_HALF * 5.0 * (nextblock_id - 1) as f32;
-            // 初期位置y座標反映＋ブロックタイプごとのオフセット計算
-            let init_position = blocktype.calculate_position(BLOCK_INIT_POSITION.with_y(y));
-
-            // インデックスからブロックの座標を計算し、位置を更新
-            transform.translation = Vec3::new(
-                init_position.x + GRID_SIZE_HALF * ((index % 4) as f32),
-                init_position.y - GRID_SIZE_HALF * ((index / 4) as f32),
-                10.0,
-            );
-        }
+        // 形状データ内に該当するセルを検索
+        let Some(cell) = pieces.shapes(blocktype)[0].get(block_id - 1) else {
+            // セルの数が少ないブロックでは、余ったエンティティを隠す
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+        *visibility = Visibility::Inherited;
+
+        // ブロックの描画y座標を計算
+        let y = block_init_position.y - GRID_SIZE_HALF * 5.0 * (nextblock_id - 1) as f32;
+        // 初期位置y座標反映＋ブロックタイプごとのオフセット計算
+        let init_position = pieces.preview_position(blocktype, block_init_position.with_y(y));
+
+        // セルの座標からブロックの座標を計算し、位置を更新
+        transform.translation = Vec3::new(
+            init_position.x + GRID_SIZE_HALF * cell.x as f32,
+            init_position.y - GRID_SIZE_HALF * cell.y as f32,
+            10.0,
+        );
     }
 }
 
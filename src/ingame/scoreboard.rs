@@ -1,102 +1,190 @@
-use bevy::prelude::*;
+use bevy::{
+    ecs::system::SystemParam,
+    prelude::*,
+};
 
 use crate::{
     GRID_SIZE_HALF,
     PATH_FONT,
     AppState,
     Score,
+    ClearedLines,
+    PlacedPieces,
+    Level,
+};
+use crate::gamemode::{
+    format_time,
+    GameMode,
+    Goal,
+    Grade,
+    HudItem,
+    PlayTime,
 };
 use super::{
-    FIELD_SIZE,
-    FIELD_POSITION,
+    holdblock,
+    nextblock,
 };
+use super::utils::prelude::*;
 
 const BOARD_SIZE: Vec2 = Vec2::new(
-    GRID_SIZE_HALF * 6.0,
+    SIDE_BOARD_WIDTH,
     GRID_SIZE_HALF * 10.0,
 );
-const BOARD_POSITION: Vec3 = Vec3::new(
-    FIELD_POSITION.x - FIELD_SIZE.x / 2.0 - BOARD_SIZE.x / 2.0,
-    FIELD_POSITION.y - FIELD_SIZE.y / 2.0 + BOARD_SIZE.y / 2.0,
-    0.0,
-);
 const BOARD_COLOR: Color = Color::srgb(0.16, 0.18, 0.26);
 
-const TITLE_TEXT: &str = "SCORE";
-const TITLE_POSITION: Vec3 = Vec3::new(
-    BOARD_POSITION.x, 
-    BOARD_POSITION.y + BOARD_SIZE.y / 2.0 - TEXT_SIZE / 2.0 - GRID_SIZE_HALF * 1.75,
-    10.0,
-);
-
-const SCORE_POSITION: Vec3 = Vec3::new(
-    BOARD_POSITION.x, 
-    BOARD_POSITION.y - BOARD_SIZE.y / 2.0 + TEXT_SIZE / 2.0 + GRID_SIZE_HALF * 1.75,
-    10.0,
-);
+/// ボードの中心からタイトル、値までの縦方向の距離
+const TITLE_OFFSET: f32 = BOARD_SIZE.y / 2.0 - TEXT_SIZE / 2.0 - GRID_SIZE_HALF * 1.75;
+const VALUE_OFFSET: f32 = -TITLE_OFFSET;
 
 const TEXT_SIZE: f32 = 20.0;
 
 #[derive(Component)]
 struct Scoreboard;
 
-/// スコアの点数の更新をするためのコンポーネント
-#[derive(Component)]
-struct ScoreText;
+/// ボードに表示する値をまとめたシステムパラメータ
+#[derive(SystemParam)]
+struct HudValues<'w> {
+    score: Res<'w, Score>,
+    level: Res<'w, Level>,
+    lines: Res<'w, ClearedLines>,
+    pieces: Res<'w, PlacedPieces>,
+    playtime: Res<'w, PlayTime>,
+    grade: Res<'w, Grade>,
+    blockmap: Res<'w, BlockMap>,
+    mode: Res<'w, GameMode>,
+}
 
-/// スコアボードのセットアップを行う関数
-fn setup(
-    mut commands: Commands,
-    score: Res<Score>,
-    asset_server: Res<AssetServer>,
+/// ボードに表示する値の更新をするためのコンポーネント
+#[derive(Component)]
+struct HudValue(HudItem);
+
+/// ボードとタイトル、値のテキストを生成する関数
+///
+/// # Arguments
+/// * position - ボードの中心の位置
+/// * title - ボード上部に表示するタイトル
+/// * value - ボード下部に表示する値
+/// * marker - 値のテキストを更新するためのコンポーネント
+fn spawn_board(
+    commands: &mut Commands,
+    font: &Handle<Font>,
+    position: Vec3,
+    title: &str,
+    value: String,
+    marker: impl Component,
 ) {
-    info_once!("setup");
-
-    let font = asset_server.load(PATH_FONT);
-
     // ボードを生成する
     commands.spawn((
         Sprite::from_color(BOARD_COLOR, BOARD_SIZE),
-        Transform::from_translation(BOARD_POSITION),
+        Transform::from_translation(position),
         Scoreboard,
     ));
 
     // タイトルを生成する
     commands.spawn((
-        Text2d::new(TITLE_TEXT),
+        Text2d::new(title),
         TextFont {
             font: font.clone(),
             font_size: TEXT_SIZE,
             ..Default::default()
         },
-        Transform::from_translation(TITLE_POSITION),
+        Transform::from_xyz(position.x, position.y + TITLE_OFFSET, 10.0),
         Scoreboard,
     ));
 
-    // スコアを生成する
+    // 値を生成する
     commands.spawn((
-        Text2d::new(score.0.to_string()),
+        Text2d::new(value),
         TextFont {
             font: font.clone(),
             font_size: TEXT_SIZE,
             ..Default::default()
         },
-        Transform::from_translation(SCORE_POSITION),
+        Transform::from_xyz(position.x, position.y + VALUE_OFFSET, 10.0),
         Scoreboard,
-        ScoreText,
+        marker,
     ));
 }
 
-/// スコアを更新する関数
-fn update_score(
-    mut query: Query<&mut Text2d, With<ScoreText>>,
-    score: Res<Score>,
-) -> Result {
+/// 先頭のボードの中心の位置を返す関数
+/// フィールドの左下に合わせ、フィールドが低い場合はHOLDの下に並べる
+fn left_board_position(layout: &FieldLayout) -> Vec3 {
+    let hold = holdblock::board_position(layout);
+    let y = (layout.bottom() + BOARD_SIZE.y / 2.0)
+        .min(hold.y - holdblock::BOARD_SIZE.y / 2.0 - BOARD_SIZE.y / 2.0);
+    Vec3::new(hold.x, y, 0.0)
+}
+
+/// 2つ目以降のボードの中心の位置を返す関数
+/// NEXTの下に順番に並べる
+fn right_board_position(layout: &FieldLayout, index: usize) -> Vec3 {
+    let next = nextblock::board_position(layout);
+    let top = next.y - nextblock::BOARD_SIZE.y / 2.0;
+    Vec3::new(
+        next.x,
+        top - BOARD_SIZE.y * (index as f32 + 0.5),
+        0.0,
+    )
+}
+
+/// ボードに表示する値を文字列で返す関数
+///
+/// # Arguments
+/// * item - 表示する値の種類
+/// * values - 表示する値と、残りのライン数や時間を計算するためのゲームモード
+fn hud_text(item: HudItem, values: &HudValues) -> String {
+    let HudValues { score, level, lines, pieces, playtime, grade, blockmap, mode } = values;
+    match (item, mode.goal()) {
+        (HudItem::Score, _) => score.0.to_string(),
+        (HudItem::Level, _) => level.0.to_string(),
+        (HudItem::Lines, _) => lines.0.to_string(),
+        (HudItem::Pieces, _) => pieces.0.to_string(),
+        (HudItem::GarbageLeft, _) => blockmap.count_rows_with(GARBAGE_ID).to_string(),
+        (HudItem::Grade, _) => grade.name().to_string(),
+        (HudItem::Section, _) => {
+            // 次にレベルが止まるセクションの区切りを一緒に表示する
+            let next = ((level.0 / TGM_SECTION_LEVELS + 1) * TGM_SECTION_LEVELS).min(TGM_MAX_LEVEL);
+            format!("{}/{}", level.0, next)
+        }
+        (HudItem::LinesLeft, Goal::Lines(goal)) => goal.saturating_sub(lines.0).to_string(),
+        (HudItem::TimeLeft, Goal::Time(limit)) => format_time(limit - playtime.elapsed_secs()),
+        (HudItem::Time | HudItem::LinesLeft | HudItem::TimeLeft, _) => format_time(playtime.elapsed_secs()),
+    }
+}
+
+/// スコアボードのセットアップを行う関数
+/// ゲームモードで表示する値ごとにボードを生成する
+fn setup(
+    mut commands: Commands,
+    values: HudValues,
+    asset_server: Res<AssetServer>,
+    ruleset: Res<Ruleset>,
+) {
+    info_once!("setup");
+
+    let font = asset_server.load(PATH_FONT);
+    let layout = ruleset.layout();
+
+    for (index, item) in values.mode.hud().iter().enumerate() {
+        let position = match index {
+            0 => left_board_position(&layout),
+            _ => right_board_position(&layout, index - 1),
+        };
+        let value = hud_text(*item, &values);
+        spawn_board(&mut commands, &font, position, item.title(), value, HudValue(*item));
+    }
+}
+
+/// ボードに表示している値を更新する関数
+fn update(
+    mut query: Query<(&mut Text2d, &HudValue)>,
+    values: HudValues,
+) {
     info_once!("update");
 
-    let mut span = query.single_mut()?;
-    **span = score.0.to_string();
-    Ok(())
+    for (mut span, value) in &mut query {
+        **span = hud_text(value.0, &values);
+    }
 }
 
 /// スコアボードを削除する関数
@@ -115,7 +203,7 @@ impl Plugin for ScoreboardPlugin {
     fn build(&self, app: &mut App) {
         app
             .add_systems(OnEnter(AppState::InGame), setup)
-            .add_systems(Update, update_score.run_if(in_state(AppState::InGame)))
+            .add_systems(Update, update.run_if(in_state(AppState::InGame)))
             .add_systems(OnExit(AppState::Gameover), despawn)
         ;
     }
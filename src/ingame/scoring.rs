@@ -0,0 +1,326 @@
+use bevy::prelude::*;
+
+use crate::{
+    AppState,
+    Score,
+    ClearedLines,
+    PlacedPieces,
+    Level,
+};
+use super::{
+    GamePhase,
+    BlockDropped,
+    LinesCleared,
+    PerfectCleared,
+    Spin,
+};
+use super::utils::prelude::*;
+
+/// B2Bボーナスの倍率（分子/分母）
+const B2B_NUMERATOR: usize = 3;
+const B2B_DENOMINATOR: usize = 2;
+/// コンボ1回あたりの点数
+const COMBO_SCORE: usize = 50;
+/// B2Bのテトリスでパーフェクトクリアした時の点数
+const B2B_TETRIS_PERFECT_CLEAR_SCORE: usize = 3200;
+/// ソフトドロップ、ハードドロップ1マスあたりの点数
+const SOFT_DROP_SCORE: usize = 1;
+const HARD_DROP_SCORE: usize = 2;
+
+/// ライン消去の種類ごとの基本点数を返す関数（ガイドライン準拠）
+///
+/// # Arguments
+/// * count - 消去したライン数
+/// * spin - Tスピンの種類
+fn base_score(count: usize, spin: Spin) -> usize {
+    match (spin, count) {
+        (Spin::None, 0) => 0,
+        (Spin::None, 1) => 100,
+        (Spin::None, 2) => 300,
+        (Spin::None, 3) => 500,
+        (Spin::None, _) => 800,
+        (Spin::Mini, 0) => 100,
+        (Spin::Mini, 1) => 200,
+        (Spin::Mini, _) => 400,
+        (Spin::Full, 0) => 400,
+        (Spin::Full, 1) => 800,
+        (Spin::Full, 2) => 1200,
+        (Spin::Full, _) => 1600,
+    }
+}
+
+/// 消去したライン数ごとの基本点数を返す関数（NES版テトリス準拠）
+///
+/// # Arguments
+/// * count - 消去したライン数
+fn nes_base_score(count: usize) -> usize {
+    match count {
+        0 => 0,
+        1 => 40,
+        2 => 100,
+        3 => 300,
+        _ => 1200,
+    }
+}
+
+/// パーフェクトクリアのボーナス点数を返す関数（ガイドライン準拠）
+///
+/// # Arguments
+/// * count - 消去したライン数
+/// * b2b - B2Bのボーナス対象かどうか
+fn perfect_clear_score(count: usize, b2b: bool) -> usize {
+    match count {
+        0 => 0,
+        1 => 800,
+        2 => 1200,
+        3 => 1800,
+        _ if b2b => B2B_TETRIS_PERFECT_CLEAR_SCORE,
+        _ => 2000,
+    }
+}
+
+/// ライン消去の点数を返す関数
+/// ガイドラインでは基本点数にレベルを掛け、B2Bとコンボのボーナスを加える
+/// NES版では基本点数に（レベル + 1）を掛ける
+/// 点数を計算しないルールでは0になる
+///
+/// # Arguments
+/// * scoring - 点数の計算方法
+/// * level - 消去前のレベル
+/// * cleared - ライン消去の内容
+fn lines_score(scoring: ScoringKind, level: usize, cleared: &LinesCleared) -> usize {
+    match scoring {
+        ScoringKind::Guideline => {
+            // レベル0から始まるルールでも点数が入るように、倍率は1以上にする
+            let multiplier = level.max(1);
+
+            // 基本点数を計算し、B2Bであれば1.5倍にする
+            let mut points = base_score(cleared.count, cleared.spin) * multiplier;
+            if cleared.b2b {
+                points = points * B2B_NUMERATOR / B2B_DENOMINATOR;
+            }
+
+            // コンボのボーナスを加える
+            points + COMBO_SCORE * cleared.combo * multiplier
+        }
+        ScoringKind::Nes => nes_base_score(cleared.count) * (level + 1),
+        ScoringKind::None => 0,
+    }
+}
+
+/// ゲーム開始時のレベルを設定する関数
+fn setup_level(
+    mut level: ResMut<Level>,
+    lines: Res<ClearedLines>,
+    ruleset: Res<Ruleset>,
+) {
+    info_once!("setup_level");
+
+    **level = ruleset.level(**lines);
+}
+
+/// ライン消去時にスコアを加算する関数
+/// スコアは消去前のレベルで計算し、その後にレベルを更新する
+fn add_lines_score(
+    cleared: On<LinesCleared>,
+    mut score: ResMut<Score>,
+    mut lines: ResMut<ClearedLines>,
+    mut level: ResMut<Level>,
+    ruleset: Res<Ruleset>,
+) {
+    info_once!("add_lines_score");
+
+    if cleared.count > 0 {
+        debug!(
+            "lines cleared: count={} spin={:?} b2b={} combo={} perfect_clear={}",
+            cleared.count, cleared.spin, cleared.b2b, cleared.combo, cleared.perfect_clear,
+        );
+    }
+
+    **score += lines_score(ruleset.scoring, **level, &cleared);
+    **lines += cleared.count;
+
+    // ルールに合わせてレベルを更新
+    let next = ruleset.next_level(**level, **lines, cleared.count);
+    if next != **level {
+        debug!("level up: {} -> {}", **level, next);
+        **level = next;
+    }
+}
+
+/// パーフェクトクリア時にボーナスを加算する関数
+/// ライン消去の点数とは別に、イベントで受け取った消去前のレベルを掛けたボーナスを加える
+/// 対戦ルールでは攻撃のライン数だけ、せり上がりを待っているおじゃまブロックを相殺する
+fn add_perfect_clear_score(
+    cleared: On<PerfectCleared>,
+    mut score: ResMut<Score>,
+    mut garbage: ResMut<GarbageQueue>,
+    ruleset: Res<Ruleset>,
+) {
+    info_once!("add_perfect_clear_score");
+
+    debug!("perfect clear: count={} b2b={}", cleared.count, cleared.b2b);
+
+    // パーフェクトクリアのボーナスがないルールでは加算しない
+    if ruleset.scoring != ScoringKind::Guideline {
+        return;
+    }
+
+    **score += perfect_clear_score(cleared.count, cleared.b2b) * cleared.level.max(1);
+
+    if ruleset.perfect_clear_attack > 0 {
+        garbage.cancel(ruleset.perfect_clear_attack);
+    }
+}
+
+/// 置いたブロックの数を数える関数
+/// ブロックが固定されるたびに送信されるライン消去イベントで数える
+fn count_pieces(
+    _cleared: On<LinesCleared>,
+    mut pieces: ResMut<PlacedPieces>,
+) {
+    info_once!("count_pieces");
+
+    **pieces += 1;
+}
+
+/// 次のブロックが出現した時にレベルを更新する関数
+/// 出現待ちが終わった時に実行されるため、ホールドで入れ替えたブロックは数えない
+fn advance_level_on_spawn(
+    mut level: ResMut<Level>,
+    ruleset: Res<Ruleset>,
+) {
+    info_once!("advance_level_on_spawn");
+
+    // レベルが変わった時だけ更新して、落下速度やセクションタイムの更新を起こさない
+    let next = ruleset.spawn_level(**level);
+    if next != **level {
+        **level = next;
+    }
+}
+
+/// ソフトドロップ、ハードドロップ時にスコアを加算する関数
+/// 点数を計算しないルールでは加算しない
+fn add_drop_score(
+    dropped: On<BlockDropped>,
+    mut score: ResMut<Score>,
+    ruleset: Res<Ruleset>,
+) {
+    info_once!("add_drop_score");
+
+    if ruleset.scoring == ScoringKind::None {
+        return;
+    }
+
+    let points = if dropped.hard { HARD_DROP_SCORE } else { SOFT_DROP_SCORE };
+    **score += points * dropped.distance;
+}
+
+pub struct ScoringPlugin;
+
+impl Plugin for ScoringPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(OnEnter(AppState::InGame), setup_level)
+            .add_systems(OnExit(GamePhase::Spawning), advance_level_on_spawn)
+            .add_observer(add_lines_score)
+            .add_observer(add_perfect_clear_score)
+            .add_observer(add_drop_score)
+            .add_observer(count_pieces)
+        ;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// ライン消去イベントを作る関数
+    fn cleared(count: usize, spin: Spin, b2b: bool, combo: usize) -> LinesCleared {
+        LinesCleared { count, spin, b2b, combo, perfect_clear: false }
+    }
+
+    #[test]
+    fn guideline_base_scores() {
+        let table = [
+            (0, Spin::None, 0),
+            (1, Spin::None, 100),
+            (2, Spin::None, 300),
+            (3, Spin::None, 500),
+            (4, Spin::None, 800),
+            (0, Spin::Mini, 100),
+            (1, Spin::Mini, 200),
+            (2, Spin::Mini, 400),
+            (0, Spin::Full, 400),
+            (1, Spin::Full, 800),
+            (2, Spin::Full, 1200),
+            (3, Spin::Full, 1600),
+        ];
+        for (count, spin, expected) in table {
+            assert_eq!(base_score(count, spin), expected, "{} lines, {:?}", count, spin);
+        }
+    }
+
+    #[test]
+    fn guideline_lines_score_with_level_b2b_and_combo() {
+        let table = [
+            // (レベル, ライン消去, 点数)
+            (1, cleared(4, Spin::None, false, 0), 800),
+            (5, cleared(4, Spin::None, false, 0), 4000),
+            // レベル0でも倍率は1
+            (0, cleared(1, Spin::None, false, 0), 100),
+            // B2Bは1.5倍
+            (2, cleared(4, Spin::None, true, 0), 2400),
+            (1, cleared(2, Spin::Full, true, 0), 1800),
+            (1, cleared(1, Spin::Mini, true, 0), 300),
+            // コンボは50 × コンボ数 × レベル（B2Bの倍率はかからない）
+            (2, cleared(1, Spin::None, false, 3), 500),
+            (3, cleared(4, Spin::None, true, 1), 3750),
+            // ラインを消さないTスピン
+            (4, cleared(0, Spin::Full, false, 0), 1600),
+        ];
+        for (level, cleared, expected) in table {
+            assert_eq!(lines_score(ScoringKind::Guideline, level, &cleared), expected, "level {}, {:?}", level, cleared);
+        }
+    }
+
+    #[test]
+    fn nes_lines_score_multiplies_by_level_plus_one() {
+        let table = [
+            (0, 1, 40),
+            (0, 2, 100),
+            (0, 3, 300),
+            (0, 4, 1200),
+            (9, 4, 12000),
+            (19, 1, 800),
+            (5, 0, 0),
+        ];
+        for (level, count, expected) in table {
+            // NES版ではTスピン、B2B、コンボのボーナスはない
+            let cleared = cleared(count, Spin::Full, true, 2);
+            assert_eq!(lines_score(ScoringKind::Nes, level, &cleared), expected, "level {}, {} lines", level, count);
+        }
+    }
+
+    #[test]
+    fn no_scoring_gives_zero() {
+        let cleared = cleared(4, Spin::None, true, 3);
+        assert_eq!(lines_score(ScoringKind::None, 500, &cleared), 0);
+    }
+
+    #[test]
+    fn perfect_clear_scores() {
+        let table = [
+            (1, false, 800),
+            (2, false, 1200),
+            (3, false, 1800),
+            (4, false, 2000),
+            (4, true, 3200),
+            // B2Bのボーナスはテトリスのみ
+            (1, true, 800),
+        ];
+        for (count, b2b, expected) in table {
+            assert_eq!(perfect_clear_score(count, b2b), expected, "{} lines, b2b {}", count, b2b);
+        }
+    }
+}
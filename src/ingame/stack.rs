@@ -0,0 +1,128 @@
+use bevy::prelude::*;
+use bevy::time::Stopwatch;
+
+use crate::AppState;
+use super::LinesCleared;
+use super::utils::prelude::*;
+
+/// ゲームオーバー時に固定ブロックを再表示し始めてからの時間を管理するリソース
+#[derive(Resource, Deref, DerefMut)]
+struct StackRevealTimer(Stopwatch);
+
+/// 見えなくなる固定ブロックのマテリアルを複製する関数
+/// 同じブロックのマスは1つのマテリアルを共有しているため、行ごとに不透明度を変えられるようにする
+fn separate_material(
+    add: On<Add, BlockLifetime>,
+    mut query: Query<&mut MeshMaterial2d<ColorMaterial>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    info_once!("separate_material");
+
+    let Ok(mut material) = query.get_mut(add.entity) else {
+        return;
+    };
+    if let Some(color) = materials.get(&material.0).map(|material| material.color) {
+        material.0 = materials.add(color);
+    }
+}
+
+/// 固定ブロックが見えなくなるまでの時間を進める関数
+fn tick_lifetime(
+    mut query: Query<&mut BlockLifetime>,
+    time: Res<Time>,
+) {
+    info_once!("tick_lifetime");
+
+    for mut lifetime in &mut query {
+        lifetime.elapsed += time.delta_secs();
+    }
+}
+
+/// ライン消去時に、見えなくなった固定ブロックを一時的に再表示する関数
+fn reveal_on_clear(
+    cleared: On<LinesCleared>,
+    mut query: Query<&mut BlockLifetime>,
+) {
+    info_once!("reveal_on_clear");
+
+    if cleared.count == 0 {
+        return;
+    }
+    for mut lifetime in &mut query {
+        lifetime.reveal(STACK_REVEAL_TIME);
+    }
+}
+
+/// 固定ブロックの不透明度をマテリアルに反映する関数
+fn update_alpha(
+    query: Query<(&BlockLifetime, &MeshMaterial2d<ColorMaterial>)>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    info_once!("update_alpha");
+
+    for (lifetime, material) in &query {
+        set_alpha(&mut materials, material, lifetime.alpha());
+    }
+}
+
+/// ゲームオーバー時に固定ブロックの再表示を始める関数
+fn start_reveal(mut timer: ResMut<StackRevealTimer>) {
+    info_once!("start_reveal");
+
+    timer.reset();
+}
+
+/// ゲームオーバー時に固定ブロックを下の行から順に再表示する関数
+/// 各行は`STACK_REVEAL_ROW_DELAY`ずつ遅れて、`STACK_FADE_DURATION`かけて現れる
+fn reveal_on_gameover(
+    query: Query<(&Block, &BlockLifetime, &MeshMaterial2d<ColorMaterial>)>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut timer: ResMut<StackRevealTimer>,
+    blockmap: Res<BlockMap>,
+    time: Res<Time>,
+) {
+    info_once!("reveal_on_gameover");
+
+    timer.tick(time.delta());
+    let bottom = blockmap.height() as i32 - 1;
+    for (block, lifetime, material) in &query {
+        let start = (bottom - block.0.y) as f32 * STACK_REVEAL_ROW_DELAY;
+        let progress = ((timer.elapsed_secs() - start) / STACK_FADE_DURATION).clamp(0.0, 1.0);
+        set_alpha(&mut materials, material, lifetime.alpha().max(progress));
+    }
+}
+
+/// マテリアルの不透明度を変更する関数
+/// 変更がない場合はマテリアルを更新しない
+fn set_alpha(
+    materials: &mut Assets<ColorMaterial>,
+    material: &MeshMaterial2d<ColorMaterial>,
+    alpha: f32,
+) {
+    let changed = materials.get(&material.0)
+        .is_some_and(|material| material.color.alpha() != alpha);
+    if !changed {
+        return;
+    }
+    if let Some(material) = materials.get_mut(&material.0) {
+        material.color.set_alpha(alpha);
+    }
+}
+
+pub struct StackPlugin;
+
+impl Plugin for StackPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(StackRevealTimer(Stopwatch::new()))
+            .add_observer(separate_material)
+            .add_observer(reveal_on_clear)
+            .add_systems(Update, (tick_lifetime, update_alpha)
+                .chain()
+                .run_if(in_state(AppState::InGame))
+            )
+            .add_systems(OnEnter(AppState::Gameover), start_reveal)
+            .add_systems(Update, reveal_on_gameover.run_if(in_state(AppState::Gameover)))
+        ;
+    }
+}
@@ -1,50 +1,39 @@
 use bevy::prelude::*;
 
 use crate::GRID_SIZE;
-use super::fielddata::{
-    FIELD_SIZE,
-    FIELD_POSITION,
-};
 
 pub const MAX_BLOCK_COUNT: usize = 4;
 pub const MAX_COLLISION_COUNT: usize = 3;
 pub const BLOCK_SIZE: f32 = GRID_SIZE - 1.0;
-pub const BLOCK_POSITION: Vec3 = Vec3::new(
-    FIELD_POSITION.x + GRID_SIZE / 2.0 - GRID_SIZE * 2.0,
-    FIELD_POSITION.y + GRID_SIZE / 2.0 + FIELD_SIZE.y / 2.0 - GRID_SIZE * 1.0,
-    10.0,
-);
+pub const BLOCK_DEPTH: f32 = 10.0;
 pub const BLOCK_FALL_SPEED: f32 = 0.5;
-pub const BLOCK_MOVE_SPEED: f32 = 0.225;
+pub const BLOCK_DAS: f32 = 0.167;
+pub const BLOCK_ARR: f32 = 0.033;
+pub const BLOCK_DAS_CUT: f32 = 0.0;
+pub const SOFT_DROP_FACTOR: f32 = 20.0;
+pub const BLOCK_LOCK_DELAY: f32 = 0.5;
+pub const MAX_LOCK_RESET_COUNT: usize = 15;
+pub const BLOCK_ARE: f32 = 0.1;
+pub const LINE_CLEAR_DELAY: f32 = 0.3;
+pub const START_LEVEL: usize = 1;
+pub const LINES_PER_LEVEL: usize = 10;
+/// TGMのレベルの上限と、レベルが止まる区切り（セクション）のレベル数
+pub const TGM_MAX_LEVEL: usize = 999;
+pub const TGM_SECTION_LEVELS: usize = 100;
+/// NES版テトリスで最初にレベルが上がるまでのライン数の計算に使用する値
+pub const NES_FIRST_LEVEL_UP_OFFSET: usize = 50;
+pub const NES_FIRST_LEVEL_UP_MIN: usize = 100;
+/// NES版テトリスの出現待ちで、固定した高さ（下から数えた行）に足す行数、
+/// 出現待ちが長くなる行数の間隔、1段階で長くなるフレーム数と段階の上限
+pub const NES_ARE_HEIGHT_OFFSET: usize = 2;
+pub const NES_ARE_ROWS_PER_STEP: usize = 4;
+pub const NES_ARE_STEP_FRAMES: f32 = 2.0;
+pub const NES_ARE_MAX_STEPS: usize = 4;
 pub const NEXT_BLOCK_COUNT: usize = 4;
 
-pub const BLOCK_UNIT_COUNT: usize = 4;
-pub const BLOCK_MAP: [[usize; 10]; 24] = [
-    [0,0,0,0,0,0,0,0,0,0],
-    [0,0,0,0,0,0,0,0,0,0],
-    [0,0,0,0,0,0,0,0,0,0],
-    [0,0,0,0,0,0,0,0,0,0],
-    [0,0,0,0,0,0,0,0,0,0],
-    [0,0,0,0,0,0,0,0,0,0],
-    [0,0,0,0,0,0,0,0,0,0],
-    [0,0,0,0,0,0,0,0,0,0],
-    [0,0,0,0,0,0,0,0,0,0],
-    [0,0,0,0,0,0,0,0,0,0],
-    [0,0,0,0,0,0,0,0,0,0],
-    [0,0,0,0,0,0,0,0,0,0],
-    [0,0,0,0,0,0,0,0,0,0],
-    [0,0,0,0,0,0,0,0,0,0],
-    [0,0,0,0,0,0,0,0,0,0],
-    [0,0,0,0,0,0,0,0,0,0],
-    [0,0,0,0,0,0,0,0,0,0],
-    [0,0,0,0,0,0,0,0,0,0],
-    [0,0,0,0,0,0,0,0,0,0],
-    [0,0,0,0,0,0,0,0,0,0],
-    [0,0,0,0,0,0,0,0,0,0],
-    [0,0,0,0,0,0,0,0,0,0],
-    [0,0,0,0,0,0,0,0,0,0],
-    [0,0,0,0,0,0,0,0,0,0],
-];
+pub const BOARD_WIDTH: usize = 10;
+pub const BOARD_VISIBLE_HEIGHT: usize = 20;
+pub const BUFFER_HEIGHT: usize = 4;
 pub const I_BLOCK: [[usize; 16]; 4] = [
     [
         0,0,0,0,
@@ -151,10 +140,10 @@ pub const O_BLOCK: [[usize; 16]; 4] = [
 ];
 pub const S_BLOCK: [[usize; 16]; 4] = [
     [
-        0,0,0,0,
         0,1,2,0,
         3,4,0,0,
         0,0,0,0,
+        0,0,0,0,
     ],
     [
         0,1,0,0,
@@ -163,23 +152,193 @@ pub const S_BLOCK: [[usize; 16]; 4] = [
         0,0,0,0,
     ],
     [
-        0,2,1,0,
+        0,0,0,0,
+        0,1,2,0,
         3,4,0,0,
         0,0,0,0,
+    ],
+    [
+        1,0,0,0,
+        2,3,0,0,
+        0,4,0,0,
+        0,0,0,0,
+    ],
+];
+pub const T_BLOCK: [[usize; 16]; 4] = [
+    [
+        0,1,0,0,
+        2,3,4,0,
+        0,0,0,0,
+        0,0,0,0,
+    ],
+    [
+        0,1,0,0,
+        0,2,3,0,
+        0,4,0,0,
+        0,0,0,0,
+    ],
+    [
+        0,0,0,0,
+        1,2,3,0,
+        0,4,0,0,
         0,0,0,0,
     ],
     [
         0,1,0,0,
+        2,3,0,0,
+        0,4,0,0,
+        0,0,0,0,
+    ],
+];
+pub const Z_BLOCK: [[usize; 16]; 4] = [
+    [
+        1,2,0,0,
+        0,3,4,0,
+        0,0,0,0,
+        0,0,0,0,
+    ],
+    [
+        0,0,1,0,
         0,2,3,0,
+        0,4,0,0,
+        0,0,0,0,
+    ],
+    [
+        0,0,0,0,
+        1,2,0,0,
+        0,3,4,0,
+        0,0,0,0,
+    ],
+    [
+        0,1,0,0,
+        2,3,0,0,
+        4,0,0,0,
+        0,0,0,0,
+    ],
+];
+/// ARS（アリカ・ローテーションシステム）の形状データ
+/// 回転状態が異なっても、ブロックの底の位置が揃うように定義されている
+pub const ARS_I_BLOCK: [[usize; 16]; 4] = [
+    [
+        0,0,0,0,
+        1,2,3,4,
+        0,0,0,0,
+        0,0,0,0,
+    ],
+    [
+        0,0,1,0,
+        0,0,2,0,
+        0,0,3,0,
         0,0,4,0,
+    ],
+    [
+        0,0,0,0,
+        1,2,3,4,
         0,0,0,0,
+        0,0,0,0,
+    ],
+    [
+        0,0,1,0,
+        0,0,2,0,
+        0,0,3,0,
+        0,0,4,0,
     ],
 ];
-pub const T_BLOCK: [[usize; 16]; 4] = [
+pub const ARS_J_BLOCK: [[usize; 16]; 4] = [
+    [
+        0,0,0,0,
+        1,2,3,0,
+        0,0,4,0,
+        0,0,0,0,
+    ],
     [
         0,1,0,0,
+        0,2,0,0,
+        3,4,0,0,
+        0,0,0,0,
+    ],
+    [
+        0,0,0,0,
+        1,0,0,0,
         2,3,4,0,
         0,0,0,0,
+    ],
+    [
+        0,1,2,0,
+        0,3,0,0,
+        0,4,0,0,
+        0,0,0,0,
+    ],
+];
+pub const ARS_L_BLOCK: [[usize; 16]; 4] = [
+    [
+        0,0,0,0,
+        1,2,3,0,
+        4,0,0,0,
+        0,0,0,0,
+    ],
+    [
+        1,2,0,0,
+        0,3,0,0,
+        0,4,0,0,
+        0,0,0,0,
+    ],
+    [
+        0,0,0,0,
+        0,0,1,0,
+        2,3,4,0,
+        0,0,0,0,
+    ],
+    [
+        0,1,0,0,
+        0,2,0,0,
+        0,3,4,0,
+        0,0,0,0,
+    ],
+];
+pub const ARS_S_BLOCK: [[usize; 16]; 4] = [
+    [
+        0,0,0,0,
+        0,1,2,0,
+        3,4,0,0,
+        0,0,0,0,
+    ],
+    [
+        1,0,0,0,
+        2,3,0,0,
+        0,4,0,0,
+        0,0,0,0,
+    ],
+    [
+        0,0,0,0,
+        0,1,2,0,
+        3,4,0,0,
+        0,0,0,0,
+    ],
+    [
+        1,0,0,0,
+        2,3,0,0,
+        0,4,0,0,
+        0,0,0,0,
+    ],
+];
+pub const ARS_T_BLOCK: [[usize; 16]; 4] = [
+    [
+        0,0,0,0,
+        1,2,3,0,
+        0,4,0,0,
+        0,0,0,0,
+    ],
+    [
+        0,1,0,0,
+        2,3,0,0,
+        0,4,0,0,
+        0,0,0,0,
+    ],
+    [
+        0,0,0,0,
+        0,1,0,0,
+        2,3,4,0,
         0,0,0,0,
     ],
     [
@@ -188,6 +347,140 @@ pub const T_BLOCK: [[usize; 16]; 4] = [
         0,4,0,0,
         0,0,0,0,
     ],
+];
+pub const ARS_Z_BLOCK: [[usize; 16]; 4] = [
+    [
+        0,0,0,0,
+        1,2,0,0,
+        0,3,4,0,
+        0,0,0,0,
+    ],
+    [
+        0,0,1,0,
+        0,2,3,0,
+        0,4,0,0,
+        0,0,0,0,
+    ],
+    [
+        0,0,0,0,
+        1,2,0,0,
+        0,3,4,0,
+        0,0,0,0,
+    ],
+    [
+        0,0,1,0,
+        0,2,3,0,
+        0,4,0,0,
+        0,0,0,0,
+    ],
+];
+/// NES版テトリスの形状データ
+/// I, S, Zブロックは2状態のみで、回転軸が右寄り（右手系）になっている
+pub const NES_I_BLOCK: [[usize; 16]; 4] = [
+    [
+        0,0,0,0,
+        0,0,0,0,
+        1,2,3,4,
+        0,0,0,0,
+    ],
+    [
+        0,0,1,0,
+        0,0,2,0,
+        0,0,3,0,
+        0,0,4,0,
+    ],
+    [
+        0,0,0,0,
+        0,0,0,0,
+        1,2,3,4,
+        0,0,0,0,
+    ],
+    [
+        0,0,1,0,
+        0,0,2,0,
+        0,0,3,0,
+        0,0,4,0,
+    ],
+];
+pub const NES_J_BLOCK: [[usize; 16]; 4] = [
+    [
+        0,0,0,0,
+        1,2,3,0,
+        0,0,4,0,
+        0,0,0,0,
+    ],
+    [
+        0,1,0,0,
+        0,2,0,0,
+        3,4,0,0,
+        0,0,0,0,
+    ],
+    [
+        1,0,0,0,
+        2,3,4,0,
+        0,0,0,0,
+        0,0,0,0,
+    ],
+    [
+        0,1,2,0,
+        0,3,0,0,
+        0,4,0,0,
+        0,0,0,0,
+    ],
+];
+pub const NES_L_BLOCK: [[usize; 16]; 4] = [
+    [
+        0,0,0,0,
+        1,2,3,0,
+        4,0,0,0,
+        0,0,0,0,
+    ],
+    [
+        1,2,0,0,
+        0,3,0,0,
+        0,4,0,0,
+        0,0,0,0,
+    ],
+    [
+        0,0,1,0,
+        2,3,4,0,
+        0,0,0,0,
+        0,0,0,0,
+    ],
+    [
+        0,1,0,0,
+        0,2,0,0,
+        0,3,4,0,
+        0,0,0,0,
+    ],
+];
+pub const NES_S_BLOCK: [[usize; 16]; 4] = [
+    [
+        0,0,0,0,
+        0,1,2,0,
+        3,4,0,0,
+        0,0,0,0,
+    ],
+    [
+        0,1,0,0,
+        0,2,3,0,
+        0,0,4,0,
+        0,0,0,0,
+    ],
+    [
+        0,0,0,0,
+        0,1,2,0,
+        3,4,0,0,
+        0,0,0,0,
+    ],
+    [
+        0,1,0,0,
+        0,2,3,0,
+        0,0,4,0,
+        0,0,0,0,
+    ],
+];
+pub const NES_T_BLOCK: [[usize; 16]; 4] = [
     [
         0,0,0,0,
         1,2,3,0,
@@ -200,8 +493,74 @@ pub const T_BLOCK: [[usize; 16]; 4] = [
         0,4,0,0,
         0,0,0,0,
     ],
+    [
+        0,1,0,0,
+        2,3,4,0,
+        0,0,0,0,
+        0,0,0,0,
+    ],
+    [
+        0,1,0,0,
+        0,2,3,0,
+        0,4,0,0,
+        0,0,0,0,
+    ],
 ];
-pub const Z_BLOCK: [[usize; 16]; 4] = [
+pub const NES_Z_BLOCK: [[usize; 16]; 4] = [
+    [
+        0,0,0,0,
+        1,2,0,0,
+        0,3,4,0,
+        0,0,0,0,
+    ],
+    [
+        0,0,1,0,
+        0,2,3,0,
+        0,4,0,0,
+        0,0,0,0,
+    ],
+    [
+        0,0,0,0,
+        1,2,0,0,
+        0,3,4,0,
+        0,0,0,0,
+    ],
+    [
+        0,0,1,0,
+        0,2,3,0,
+        0,4,0,0,
+        0,0,0,0,
+    ],
+];
+/// 独自の回転で使用する、SRSに合わせる前のS, Zブロックの形状データ
+/// 0と2の状態で形状の高さが1行ずれている
+pub const CLASSIC_S_BLOCK: [[usize; 16]; 4] = [
+    [
+        0,0,0,0,
+        0,1,2,0,
+        3,4,0,0,
+        0,0,0,0,
+    ],
+    [
+        0,1,0,0,
+        0,2,3,0,
+        0,0,4,0,
+        0,0,0,0,
+    ],
+    [
+        0,2,1,0,
+        3,4,0,0,
+        0,0,0,0,
+        0,0,0,0,
+    ],
+    [
+        0,1,0,0,
+        0,2,3,0,
+        0,0,4,0,
+        0,0,0,0,
+    ],
+];
+pub const CLASSIC_Z_BLOCK: [[usize; 16]; 4] = [
     [
         0,0,0,0,
         1,2,0,0,
@@ -227,6 +586,36 @@ pub const Z_BLOCK: [[usize; 16]; 4] = [
         0,0,0,0,
     ],
 ];
+/// SRSの壁蹴りデータ
+/// 回転前の状態（0, R, 2, L）ごとに試行する位置のずれを順番に持つ
+/// ガイドラインの表はy軸が上向きのため、盤面に合わせてy軸を反転している
+pub const JLSTZ_KICKS_RIGHT: [[IVec2; 5]; 4] = [
+    [IVec2::new(0, 0), IVec2::new(-1, 0), IVec2::new(-1, -1), IVec2::new(0, 2), IVec2::new(-1, 2)],
+    [IVec2::new(0, 0), IVec2::new(1, 0), IVec2::new(1, 1), IVec2::new(0, -2), IVec2::new(1, -2)],
+    [IVec2::new(0, 0), IVec2::new(1, 0), IVec2::new(1, -1), IVec2::new(0, 2), IVec2::new(1, 2)],
+    [IVec2::new(0, 0), IVec2::new(-1, 0), IVec2::new(-1, 1), IVec2::new(0, -2), IVec2::new(-1, -2)],
+];
+pub const JLSTZ_KICKS_LEFT: [[IVec2; 5]; 4] = [
+    [IVec2::new(0, 0), IVec2::new(1, 0), IVec2::new(1, -1), IVec2::new(0, 2), IVec2::new(1, 2)],
+    [IVec2::new(0, 0), IVec2::new(1, 0), IVec2::new(1, 1), IVec2::new(0, -2), IVec2::new(1, -2)],
+    [IVec2::new(0, 0), IVec2::new(-1, 0), IVec2::new(-1, -1), IVec2::new(0, 2), IVec2::new(-1, 2)],
+    [IVec2::new(0, 0), IVec2::new(-1, 0), IVec2::new(-1, 1), IVec2::new(0, -2), IVec2::new(-1, -2)],
+];
+pub const I_KICKS_RIGHT: [[IVec2; 5]; 4] = [
+    [IVec2::new(0, 0), IVec2::new(-2, 0), IVec2::new(1, 0), IVec2::new(-2, 1), IVec2::new(1, -2)],
+    [IVec2::new(0, 0), IVec2::new(-1, 0), IVec2::new(2, 0), IVec2::new(-1, -2), IVec2::new(2, 1)],
+    [IVec2::new(0, 0), IVec2::new(2, 0), IVec2::new(-1, 0), IVec2::new(2, -1), IVec2::new(-1, 2)],
+    [IVec2::new(0, 0), IVec2::new(1, 0), IVec2::new(-2, 0), IVec2::new(1, 2), IVec2::new(-2, -1)],
+];
+pub const I_KICKS_LEFT: [[IVec2; 5]; 4] = [
+    [IVec2::new(0, 0), IVec2::new(-1, 0), IVec2::new(2, 0), IVec2::new(-1, -2), IVec2::new(2, 1)],
+    [IVec2::new(0, 0), IVec2::new(2, 0), IVec2::new(-1, 0), IVec2::new(2, -1), IVec2::new(-1, 2)],
+    [IVec2::new(0, 0), IVec2::new(1, 0), IVec2::new(-2, 0), IVec2::new(1, 2), IVec2::new(-2, -1)],
+    [IVec2::new(0, 0), IVec2::new(-2, 0), IVec2::new(1, 0), IVec2::new(-2, 1), IVec2::new(1, -2)],
+];
+pub const O_KICKS: [IVec2; 1] = [IVec2::ZERO];
+/// この壁蹴りで回転した場合、TスピンミニをTスピンとして扱う（TST蹴り）
+pub const TSPIN_UPGRADE_KICK: usize = 4;
 pub const I_COLOR: Color = Color::srgb(0.53, 0.88, 0.99);
 pub const J_COLOR: Color = Color::srgb(0.05, 0.72, 0.84);
 pub const L_COLOR: Color = Color::srgb(1.00, 0.59, 0.42);
@@ -234,3 +623,19 @@ pub const O_COLOR: Color = Color::srgb(1.00, 0.78, 0.47);
 pub const S_COLOR: Color = Color::srgb(0.31, 0.84, 0.75);
 pub const T_COLOR: Color = Color::srgb(0.75, 0.60, 1.00);
 pub const Z_COLOR: Color = Color::srgb(1.00, 0.46, 0.50);
+/// 盤面に書き込むおじゃまブロックのID（ブロックの種類のIDと重ならない値）
+pub const GARBAGE_ID: usize = usize::MAX;
+pub const GARBAGE_COLOR: Color = Color::srgb(0.45, 0.47, 0.56);
+/// 乱雑なおじゃまブロックで、1行ごとに穴の列が変わる確率
+pub const GARBAGE_MESSY_CHANCE: f64 = 0.3;
+/// ばらばらなおじゃまブロックで、穴の列以外の各マスが埋まる確率（NES版テトリスのBタイプ準拠）
+pub const GARBAGE_SCATTERED_FILL_CHANCE: f64 = 0.5;
+/// 時間でせり上がるおじゃまブロックの、1回せり上がるごとの間隔の倍率と最短の間隔（秒）
+pub const GARBAGE_TIMER_ACCELERATION: f32 = 0.95;
+pub const GARBAGE_TIMER_MIN_INTERVAL: f32 = 1.0;
+/// 固定ブロックが見えなくなり始めてから消えるまでの秒数
+pub const STACK_FADE_DURATION: f32 = 1.0;
+/// ライン消去時に、見えなくなった固定ブロックを再表示する秒数
+pub const STACK_REVEAL_TIME: f32 = 1.0;
+/// ゲームオーバー時に固定ブロックを下の行から再表示する、1行あたりの遅れ（秒）
+pub const STACK_REVEAL_ROW_DELAY: f32 = 0.05;
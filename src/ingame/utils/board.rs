@@ -0,0 +1,291 @@
+use bevy::math::IVec2;
+
+/// フィールド上のブロックの配置を整数座標で管理する盤面
+/// 座標は左上が(0, 0)で、xは右方向、yは下方向に増える
+/// 各セルには0（空き）またはブロックの種類を表すIDが格納される
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Board {
+    width: usize,
+    height: usize,
+    cells: Vec<usize>,
+}
+
+impl Board {
+    /// 指定された大きさの空の盤面を生成する
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![0; width * height],
+        }
+    }
+
+    /// 盤面の横幅を返すメソッド
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// 盤面の高さを返すメソッド
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// 指定されたセルの値を返すメソッド
+    /// 盤面の範囲外であれば`None`を返す
+    pub fn get(&self, x: i32, y: i32) -> Option<usize> {
+        if x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height {
+            return None;
+        }
+        Some(self.cells[y as usize * self.width + x as usize])
+    }
+
+    /// 指定されたセルに値を代入するメソッド
+    ///
+    /// # Panics
+    /// * 指定されたセルが盤面の範囲外の場合
+    pub fn set(&mut self, x: i32, y: i32, value: usize) {
+        assert!(self.get(x, y).is_some(), "cell out of board: ({}, {})", x, y);
+        self.cells[y as usize * self.width + x as usize] = value;
+    }
+
+    /// 指定されたセルが盤面内かつ空いているか判定するメソッド
+    pub fn is_free(&self, x: i32, y: i32) -> bool {
+        self.get(x, y) == Some(0)
+    }
+
+    /// 渡されたセルが全て盤面内の空きセルに収まるか判定するメソッド
+    pub fn fits(&self, cells: &[IVec2]) -> bool {
+        cells.iter().all(|cell| self.is_free(cell.x, cell.y))
+    }
+
+    /// 渡されたセルが衝突せずに下へ移動できる距離を返すメソッド
+    pub fn drop_distance(&self, cells: &[IVec2]) -> i32 {
+        let mut distance = 0;
+        let down = IVec2::new(0, 1);
+        loop {
+            let moved: Vec<IVec2> = cells.iter().map(|cell| *cell + down * (distance + 1)).collect();
+            if !self.fits(&moved) {
+                return distance;
+            }
+            distance += 1;
+        }
+    }
+
+    /// 渡されたセルにブロックのIDを書き込むメソッド
+    ///
+    /// # Panics
+    /// * セルが盤面の範囲外の場合
+    pub fn insert(&mut self, cells: &[IVec2], id: usize) {
+        for cell in cells {
+            self.set(cell.x, cell.y, id);
+        }
+    }
+
+    /// 指定された行が全て埋まっているか判定するメソッド
+    pub fn is_row_full(&self, y: usize) -> bool {
+        let row = &self.cells[y * self.width..(y + 1) * self.width];
+        row.iter().all(|value| *value != 0)
+    }
+
+    /// 渡された行を削除して、その行から上の値を一段下にずらすメソッド
+    ///
+    /// # Arguments
+    /// * index - 削除するブロックの行のID
+    pub fn clearline(&mut self, index: usize) {
+        // 削除する行から上の値を一段下にずらし、一番上を空の行にする
+        self.cells.copy_within(0..index * self.width, self.width);
+        self.cells[..self.width].fill(0);
+    }
+
+    /// 盤面の下から行を追加して、それより上の値を追加した行数だけ上にずらすメソッド
+    /// 盤面の一番上からはみ出たブロックがあれば`true`を返す
+    ///
+    /// # Arguments
+    /// * rows - 追加する行（上から順に並び、各行の長さは盤面の横幅と同じ）
+    ///
+    /// # Panics
+    /// * 行の長さが盤面の横幅と異なる場合
+    pub fn push_rows(&mut self, rows: &[Vec<usize>]) -> bool {
+        let count = rows.len().min(self.height);
+        let shift = count * self.width;
+        let overflow = rows.len() > self.height || self.cells[..shift].iter().any(|value| *value != 0);
+
+        // 追加する行数だけ全体を上にずらし、空いた下の行に追加する行を書き込む
+        self.cells.copy_within(shift.., 0);
+        let start = self.cells.len() - shift;
+        for (index, row) in rows[rows.len() - count..].iter().enumerate() {
+            assert!(row.len() == self.width, "row width mismatch: {}", row.len());
+            let offset = start + index * self.width;
+            self.cells[offset..offset + self.width].copy_from_slice(row);
+        }
+        overflow
+    }
+
+    /// 指定したIDのセルを含む行の数を返すメソッド
+    pub fn count_rows_with(&self, id: usize) -> usize {
+        self.cells
+            .chunks(self.width.max(1))
+            .filter(|row| row.contains(&id))
+            .count()
+    }
+
+    /// 盤面にブロックが1つもないか判定するメソッド
+    pub fn is_empty(&self) -> bool {
+        self.cells.iter().all(|value| *value == 0)
+    }
+
+    /// ブロックがある一番上の行のIDを返すメソッド
+    /// 盤面にブロックが1つもなければ`None`を返す
+    pub fn top_row(&self) -> Option<usize> {
+        self.cells
+            .iter()
+            .position(|value| *value != 0)
+            .map(|index| index / self.width)
+    }
+
+    /// 揃った行を全て削除し、削除した行のIDを上から順に返すメソッド
+    pub fn clear_full_rows(&mut self) -> Vec<usize> {
+        let rows: Vec<usize> = (0..self.height)
+            .filter(|y| self.is_row_full(*y))
+            .collect();
+        // 上の行から削除しても、それより下の行のIDは変わらない
+        for index in &rows {
+            self.clearline(*index);
+        }
+        rows
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 横一列のセルを返す関数
+    fn row(y: i32, xs: std::ops::Range<i32>) -> Vec<IVec2> {
+        xs.map(|x| IVec2::new(x, y)).collect()
+    }
+
+    #[test]
+    fn fits_inside_walls_and_floor() {
+        let board = Board::new(4, 6);
+        assert!(board.fits(&row(0, 0..4)));
+        assert!(board.fits(&row(5, 0..4)));
+        // 左右の壁
+        assert!(!board.fits(&[IVec2::new(-1, 0)]));
+        assert!(!board.fits(&[IVec2::new(4, 0)]));
+        // 床と盤面の上
+        assert!(!board.fits(&[IVec2::new(0, 6)]));
+        assert!(!board.fits(&[IVec2::new(0, -1)]));
+    }
+
+    #[test]
+    fn fits_rejects_occupied_cells() {
+        let mut board = Board::new(4, 6);
+        board.insert(&[IVec2::new(2, 3)], 1);
+        assert!(!board.fits(&[IVec2::new(2, 3)]));
+        assert!(board.fits(&[IVec2::new(1, 3)]));
+    }
+
+    #[test]
+    fn drop_distance_stops_at_floor_and_blocks() {
+        let mut board = Board::new(4, 6);
+        let cells = row(0, 0..2);
+        assert_eq!(board.drop_distance(&cells), 5);
+
+        board.insert(&[IVec2::new(1, 4)], 1);
+        assert_eq!(board.drop_distance(&cells), 3);
+        // 既に接地していれば0
+        assert_eq!(board.drop_distance(&row(3, 0..2)), 0);
+    }
+
+    #[test]
+    fn clear_full_rows_with_gap_between() {
+        let mut board = Board::new(3, 5);
+        board.insert(&row(1, 0..3), 1);
+        board.insert(&[IVec2::new(0, 2)], 2);
+        board.insert(&row(3, 0..3), 1);
+        board.insert(&[IVec2::new(2, 4)], 3);
+
+        assert_eq!(board.clear_full_rows(), vec![1, 3]);
+
+        // 間の行は下で消えた1行分だけ下がり、一番下の行はそのまま残る
+        let mut expected = Board::new(3, 5);
+        expected.insert(&[IVec2::new(0, 3)], 2);
+        expected.insert(&[IVec2::new(2, 4)], 3);
+        assert_eq!(board, expected);
+    }
+
+    #[test]
+    fn is_empty_until_a_block_is_inserted() {
+        let mut board = Board::new(3, 4);
+        assert!(board.is_empty());
+        board.insert(&[IVec2::new(1, 3)], 1);
+        assert!(!board.is_empty());
+        // 揃った行を消せば空に戻る
+        board.insert(&row(3, 0..3), 1);
+        board.clear_full_rows();
+        assert!(board.is_empty());
+    }
+
+    #[test]
+    fn top_row_is_the_highest_occupied_row() {
+        let mut board = Board::new(3, 5);
+        assert_eq!(board.top_row(), None);
+        board.insert(&[IVec2::new(0, 4)], 1);
+        assert_eq!(board.top_row(), Some(4));
+        board.insert(&[IVec2::new(2, 2)], 1);
+        assert_eq!(board.top_row(), Some(2));
+    }
+
+    #[test]
+    fn push_rows_shifts_the_stack_up() {
+        let mut board = Board::new(3, 4);
+        board.insert(&[IVec2::new(0, 3)], 1);
+        board.insert(&[IVec2::new(2, 2)], 2);
+
+        let overflow = board.push_rows(&[vec![0, 8, 8], vec![8, 0, 8]]);
+        assert!(!overflow);
+
+        // 既にあったブロックは2行上がり、追加した行は上から順に下に並ぶ
+        let mut expected = Board::new(3, 4);
+        expected.insert(&[IVec2::new(2, 0)], 2);
+        expected.insert(&[IVec2::new(0, 1)], 1);
+        expected.insert(&[IVec2::new(1, 2), IVec2::new(2, 2)], 8);
+        expected.insert(&[IVec2::new(0, 3), IVec2::new(2, 3)], 8);
+        assert_eq!(board, expected);
+    }
+
+    #[test]
+    fn push_rows_reports_blocks_pushed_off_the_top() {
+        let mut board = Board::new(3, 4);
+        board.insert(&[IVec2::new(1, 1)], 1);
+        // 1行目までは空いているので押し上げられる
+        assert!(!board.push_rows(&[vec![8, 8, 0]]));
+        // 一番上の行にあるブロックがはみ出る
+        assert!(board.push_rows(&[vec![8, 8, 0]]));
+    }
+
+    #[test]
+    fn push_rows_reports_more_rows_than_the_height() {
+        let mut board = Board::new(2, 2);
+        let overflow = board.push_rows(&[vec![1, 0], vec![2, 0], vec![3, 0]]);
+        assert!(overflow);
+
+        // 盤面に収まる下の行だけが残る
+        let mut expected = Board::new(2, 2);
+        expected.set(0, 0, 2);
+        expected.set(0, 1, 3);
+        assert_eq!(board, expected);
+    }
+
+    #[test]
+    fn count_rows_with_counts_rows_not_cells() {
+        let mut board = Board::new(3, 4);
+        assert_eq!(board.count_rows_with(8), 0);
+        board.insert(&[IVec2::new(0, 3), IVec2::new(1, 3)], 8);
+        board.insert(&[IVec2::new(2, 2)], 8);
+        board.insert(&[IVec2::new(1, 1)], 1);
+        assert_eq!(board.count_rows_with(8), 2);
+        assert_eq!(board.count_rows_with(1), 1);
+    }
+}
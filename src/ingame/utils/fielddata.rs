@@ -1,10 +1,136 @@
 use bevy::prelude::*;
 
-use crate::GRID_SIZE;
+use crate::{
+    GRID_SIZE,
+    GRID_SIZE_HALF,
+};
+use super::blockdata::BLOCK_SIZE;
 
-pub const FIELD_SIZE: Vec2 = Vec2::new(10.0 * GRID_SIZE, 20.0 * GRID_SIZE);
 pub const FIELD_POSITION: Vec3 = Vec3::new(0.0, 0.0, -10.0);
-pub const FIELD_LEFT_TOP: Vec2 = Vec2::new(
-    FIELD_POSITION.x - FIELD_SIZE.x / 2.0 + GRID_SIZE / 2.0, 
-    FIELD_POSITION.y + FIELD_SIZE.y / 2.0 - GRID_SIZE / 2.0,
-);
+/// フィールド左右に並べるボードの幅
+pub const SIDE_BOARD_WIDTH: f32 = GRID_SIZE_HALF * 6.0;
+/// フィールド右側に並べるボード（NEXT、LEVEL、LINES）の高さの合計
+pub const SIDE_BOARD_HEIGHT: f32 = GRID_SIZE_HALF * 40.0;
+
+/// フィールドの大きさと画面上の配置を計算する構造体
+/// 盤面は上から`hidden`行の見えない行（バッファゾーン）と、`height`行の見える行からなる
+/// - width: フィールドの横幅（列数）
+/// - height: 見えているフィールドの高さ（行数）
+/// - hidden: 見えているフィールドより上にある行数
+/// - scale: 盤面の1マスを画面上で何マス四方に表示するか
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FieldLayout {
+    pub width: usize,
+    pub height: usize,
+    pub hidden: usize,
+    pub scale: usize,
+}
+
+impl FieldLayout {
+    pub fn new(width: usize, height: usize, hidden: usize, scale: usize) -> Self {
+        Self { width, height, hidden, scale: scale.max(1) }
+    }
+
+    /// 盤面の1マスの画面上の大きさを返すメソッド
+    pub fn cell_size(&self) -> f32 {
+        GRID_SIZE * self.scale as f32
+    }
+
+    /// 盤面の1マスに表示するブロックの大きさを返すメソッド
+    pub fn block_size(&self) -> f32 {
+        self.cell_size() - (GRID_SIZE - BLOCK_SIZE)
+    }
+
+    /// 見えない行を含めた盤面の高さを返すメソッド
+    pub fn board_height(&self) -> usize {
+        self.height + self.hidden
+    }
+
+    /// 見えているフィールドの大きさを返すメソッド
+    pub fn size(&self) -> Vec2 {
+        Vec2::new(self.width as f32 * self.cell_size(), self.height as f32 * self.cell_size())
+    }
+
+    /// フィールドの左端のX座標を返すメソッド
+    pub fn left(&self) -> f32 {
+        FIELD_POSITION.x - self.size().x / 2.0
+    }
+
+    /// フィールドの右端のX座標を返すメソッド
+    pub fn right(&self) -> f32 {
+        FIELD_POSITION.x + self.size().x / 2.0
+    }
+
+    /// フィールドの上端のY座標を返すメソッド
+    pub fn top(&self) -> f32 {
+        FIELD_POSITION.y + self.size().y / 2.0
+    }
+
+    /// フィールドの下端のY座標を返すメソッド
+    pub fn bottom(&self) -> f32 {
+        FIELD_POSITION.y - self.size().y / 2.0
+    }
+
+    /// フィールドと左右のボードを合わせた大きさを返すメソッド
+    pub fn content_size(&self) -> Vec2 {
+        let size = self.size();
+        Vec2::new(
+            size.x + SIDE_BOARD_WIDTH * 2.0,
+            size.y.max(SIDE_BOARD_HEIGHT),
+        )
+    }
+
+    /// ブロックが出現する位置（ブロックデータの左上のセル座標）を返すメソッド
+    /// 横方向はブロックの幅で中央（左右に1マス余る場合は左寄り）、縦方向は見えている一番上の行になる
+    ///
+    /// # Arguments
+    /// * shape - 出現する向きのブロックの形状
+    pub fn spawn_position(&self, shape: &[IVec2]) -> IVec2 {
+        let left = shape.iter().map(|cell| cell.x).min().unwrap_or(0);
+        let right = shape.iter().map(|cell| cell.x).max().unwrap_or(0);
+        let x = (self.width as i32 - (right - left + 1)).max(0) / 2 - left;
+        IVec2::new(x, self.hidden as i32)
+    }
+
+    /// 盤面のセル座標からフィールド上のXY座標を計算するメソッド
+    /// 盤面の上から`hidden`行はフィールドの外（上側）に配置される
+    pub fn cell_to_translation(&self, cell: IVec2, z: f32) -> Vec3 {
+        let size = self.cell_size();
+        Vec3::new(
+            self.left() + size / 2.0 + size * cell.x as f32,
+            self.top() - size / 2.0 + size * (self.hidden as i32 - cell.y) as f32,
+            z,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ingame::utils::pieceset::PieceSet;
+
+    #[test]
+    fn spawn_position_matches_guideline_columns() {
+        let layout = FieldLayout::new(10, 20, 4, 1);
+        let pieces = PieceSet::standard();
+        for blocktype in pieces.all() {
+            let shape = &pieces.shapes(blocktype)[0];
+            assert_eq!(layout.spawn_position(shape), IVec2::new(3, 4), "{}", pieces.piece(blocktype).name);
+        }
+    }
+
+    #[test]
+    fn spawn_position_centres_pieces_on_narrow_boards() {
+        let layout = FieldLayout::new(5, 10, 2, 2);
+        let pieces = PieceSet::standard();
+        for blocktype in pieces.all() {
+            let shape = &pieces.shapes(blocktype)[0];
+            let pos = layout.spawn_position(shape);
+            let left = shape.iter().map(|cell| pos.x + cell.x).min().unwrap();
+            let right = shape.iter().map(|cell| pos.x + cell.x).max().unwrap();
+            let (gap_left, gap_right) = (left, layout.width as i32 - 1 - right);
+            assert!(gap_left >= 0 && gap_right >= 0, "{}", pieces.piece(blocktype).name);
+            assert!(gap_right - gap_left <= 1 && gap_right >= gap_left, "{}", pieces.piece(blocktype).name);
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use rand::Rng;
+
+use super::blockdata::*;
+
+/// せり上がるおじゃまブロックの穴の空き方
+/// デフォルト以外はモードから選択される
+#[allow(dead_code)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum GarbageStyle {
+    /// 1回の攻撃の間は穴の列が変わらない
+    #[default]
+    Clean,
+    /// 1行ごとに一定の確率で穴の列が変わる
+    Messy,
+    /// 1行ごとに必ず穴の列が変わる（チーズ）
+    Cheese,
+    /// 1行ごとに必ず穴の列が変わり、残りのマスも確率で空く（NES版テトリスのBタイプ）
+    Scattered,
+}
+
+impl GarbageStyle {
+    /// 1回の攻撃の中で、1行ごとに穴の列が変わる確率を返すメソッド
+    pub fn change_chance(&self) -> f64 {
+        match self {
+            GarbageStyle::Clean => 0.0,
+            GarbageStyle::Messy => GARBAGE_MESSY_CHANCE,
+            GarbageStyle::Cheese | GarbageStyle::Scattered => 1.0,
+        }
+    }
+
+    /// 穴の列を指定して、おじゃまブロックの1行を生成するメソッド
+    pub fn row(&self, width: usize, hole: usize, rng: &mut impl Rng) -> Vec<usize> {
+        match self {
+            GarbageStyle::Scattered => scattered_row(width, hole, rng),
+            _ => garbage_row(width, hole),
+        }
+    }
+}
+
+/// 穴の列以外をおじゃまブロックで埋めた1行を生成する関数
+pub fn garbage_row(width: usize, hole: usize) -> Vec<usize> {
+    (0..width)
+        .map(|x| if x == hole { 0 } else { GARBAGE_ID })
+        .collect()
+}
+
+/// 穴の列を空け、残りのマスを確率でおじゃまブロックにした1行を生成する関数
+pub fn scattered_row(width: usize, hole: usize, rng: &mut impl Rng) -> Vec<usize> {
+    (0..width)
+        .map(|x| {
+            if x != hole && rng.random_bool(GARBAGE_SCATTERED_FILL_CHANCE) {
+                GARBAGE_ID
+            } else {
+                0
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,130 @@
+/// 1秒あたりのフレーム数
+pub const FRAMES_PER_SECOND: f32 = 60.0;
+/// 重力の上限（20G）
+pub const MAX_GRAVITY: f32 = 20.0;
+/// ガイドラインの計算式で使用するレベルの上限
+const GUIDELINE_MAX_LEVEL: usize = 20;
+
+/// NES版テトリスのレベルごとの1マス落下にかかるフレーム数
+const NES_FRAMES_PER_ROW: [usize; 30] = [
+    48, 43, 38, 33, 28, 23, 18, 13, 8, 6,
+    5, 5, 5, 4, 4, 4, 3, 3, 3, 2,
+    2, 2, 2, 2, 2, 2, 2, 2, 2, 1,
+];
+
+/// TGMの内部重力（レベル, 1/256G単位の重力）
+/// レベルがその値以上になると重力が切り替わる
+const TGM_INTERNAL_GRAVITY: [(usize, usize); 30] = [
+    (0, 4), (30, 6), (35, 8), (40, 10), (50, 12),
+    (60, 16), (70, 32), (80, 48), (90, 64), (100, 80),
+    (120, 96), (140, 112), (160, 128), (170, 144), (200, 4),
+    (220, 32), (230, 64), (233, 96), (236, 128), (239, 160),
+    (243, 192), (247, 224), (251, 256), (300, 512), (330, 768),
+    (360, 1024), (400, 1280), (420, 1024), (450, 768), (500, 5120),
+];
+const TGM_GRAVITY_DENOMINATOR: f32 = 256.0;
+
+/// レベルから落下速度を決める重力曲線の種類
+/// デフォルト以外はモードから選択される
+#[allow(dead_code)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum GravityCurve {
+    /// ガイドラインの計算式（レベル1から）
+    #[default]
+    Guideline,
+    /// NES版テトリスのフレーム表（レベル0から）
+    Nes,
+    /// TGMの内部重力（レベル0から999、1G未満から20Gまで）
+    Tgm,
+}
+
+impl GravityCurve {
+    /// レベルに対応する重力（1フレームあたりに落下するマス数）を返すメソッド
+    pub fn gravity(&self, level: usize) -> f32 {
+        let gravity = match self {
+            GravityCurve::Guideline => {
+                // 1マス落下にかかる秒数 = (0.8 - (レベル - 1) * 0.007) ^ (レベル - 1)
+                let level = level.clamp(1, GUIDELINE_MAX_LEVEL) as f32 - 1.0;
+                let seconds = (0.8 - level * 0.007).powf(level);
+                1.0 / (seconds * FRAMES_PER_SECOND)
+            }
+            GravityCurve::Nes => {
+                let index = level.min(NES_FRAMES_PER_ROW.len() - 1);
+                1.0 / NES_FRAMES_PER_ROW[index] as f32
+            }
+            GravityCurve::Tgm => {
+                let (_, gravity) = TGM_INTERNAL_GRAVITY
+                    .iter()
+                    .rev()
+                    .find(|(threshold, _)| level >= *threshold)
+                    .copied()
+                    .unwrap_or(TGM_INTERNAL_GRAVITY[0]);
+                gravity as f32 / TGM_GRAVITY_DENOMINATOR
+            }
+        };
+        gravity.min(MAX_GRAVITY)
+    }
+
+    /// レベルに対応する1マス落下にかかる秒数を返すメソッド
+    pub fn interval(&self, level: usize) -> f32 {
+        1.0 / (self.gravity(level) * FRAMES_PER_SECOND)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 1マス落下にかかるフレーム数を返す関数
+    fn frames(curve: GravityCurve, level: usize) -> f32 {
+        curve.interval(level) * FRAMES_PER_SECOND
+    }
+
+    #[test]
+    fn guideline_gravity_is_clamped_to_level_1_and_20() {
+        let curve = GravityCurve::Guideline;
+        assert!((curve.interval(1) - 1.0).abs() < 1e-5);
+        assert_eq!(curve.gravity(0), curve.gravity(1));
+        assert_eq!(curve.gravity(25), curve.gravity(GUIDELINE_MAX_LEVEL));
+        assert!(curve.gravity(15) > curve.gravity(14));
+    }
+
+    #[test]
+    fn nes_frames_per_row() {
+        let table = [
+            (0, 48.0),
+            (8, 8.0),
+            (9, 6.0),
+            (18, 3.0),
+            (19, 2.0),
+            (28, 2.0),
+            (29, 1.0),
+            (40, 1.0),
+        ];
+        for (level, expected) in table {
+            assert!((frames(GravityCurve::Nes, level) - expected).abs() < 1e-3, "level {}", level);
+        }
+    }
+
+    #[test]
+    fn tgm_internal_gravity() {
+        let table = [
+            // (レベル, 1/256G単位の重力)
+            (0, 4.0),
+            (29, 4.0),
+            (30, 6.0),
+            (199, 144.0),
+            (200, 4.0),
+            (251, 256.0),
+            (420, 1024.0),
+            (499, 768.0),
+            (500, 5120.0),
+            (999, 5120.0),
+        ];
+        for (level, expected) in table {
+            assert_eq!(GravityCurve::Tgm.gravity(level), expected / TGM_GRAVITY_DENOMINATOR, "level {}", level);
+        }
+        // 20Gを上限にする
+        assert_eq!(GravityCurve::Tgm.gravity(999), MAX_GRAVITY);
+    }
+}
@@ -2,137 +2,258 @@ use bevy::{
     prelude::*,
     time::Stopwatch,
 };
+use rand::prelude::*;
+use rand_chacha::ChaCha8Rng;
+use std::collections::VecDeque;
+use std::time::Duration;
 
 use crate::{
-    GRID_SIZE,
     AppState,
+    Seed,
+};
+use super::{
+    BlockSpawned,
+    Direction,
+    GarbageRaised,
 };
-use super::BlockSpawned;
 use super::utils::{
     blockdata::*,
-    blockrandomizer::BlockRandomizer,
+    piecegenerator::{
+        GeneratorKind,
+        PieceGenerator,
+    },
+    board::Board,
+    ruleset::{
+        Ruleset,
+        StackVisibility,
+    },
+    rotationsystem::{
+        RotationKind,
+        RotationSystem,
+    },
     blocktype::BlockType,
     fielddata::*,
+    gravity::MAX_GRAVITY,
+    garbage::GarbageStyle,
+    pieceset::{
+        PieceSet,
+        PieceSetLoader,
+        Shape,
+    },
 };
 
 pub mod prelude;
 
 mod blockdata;
-mod blockrandomizer;
 mod blocktype;
+mod board;
+mod rotationsystem;
+mod ruleset;
 mod fielddata;
+mod gravity;
+mod piecegenerator;
+mod pieceset;
+mod garbage;
 
 /// 移動、回転するブロックを識別するコンポーネント
-/// 値には1~4に定義されているブロックのIDが格納される
+/// 値には形状データの順番に振られたブロックのID（1から始まる）が格納される
 #[derive(Component)]
 pub struct PlayerBlock(pub usize);
 
 /// 移動、回転しないブロックを識別するコンポーネント
-/// ブロック削除時に使用される
+/// 値には盤面上のセル座標が格納され、ブロック削除時に使用される
 #[derive(Component)]
-pub struct Block;
+pub struct Block(pub IVec2);
+
+/// 固定ブロックが見えなくなるまでの時間を管理するコンポーネント
+/// 見えなくなるルールでのみ、プレイヤーブロックを固定ブロックに変換する時に追加される
+/// - delay: 固定してから見えなくなり始めるまでの秒数
+/// - fade: 見えなくなり始めてから消えるまでの秒数（0なら一瞬で消える）
+/// - elapsed: 固定してからの経過時間（秒）
+#[derive(Component, Debug)]
+pub struct BlockLifetime {
+    pub delay: f32,
+    pub fade: f32,
+    pub elapsed: f32,
+}
 
-/// ブロック削除時に用いるリソース
-/// 値は[[usize; 10]; 24]で定義されており
-/// フィールド内の各ブロック座標が0 or 1で格納されている
-#[derive(Resource, Debug, Deref, DerefMut)]
-pub struct BlockMap(pub [[usize; 10]; 24]);
+impl BlockLifetime {
+    /// 固定したブロックの見え方から生成するメソッド
+    /// ずっと見えているルールでは`None`を返す
+    pub fn new(stack: StackVisibility) -> Option<Self> {
+        let (delay, fade) = match stack {
+            StackVisibility::Visible => return None,
+            StackVisibility::Fading(delay) => (delay, STACK_FADE_DURATION),
+            StackVisibility::Invisible => (0.0, 0.0),
+        };
+        Some(Self { delay, fade, elapsed: 0.0 })
+    }
 
-impl BlockMap {
-    /// 渡されたブロックの座標からブロックマップに値を代入するメソッド
-    ///
-    /// # Arguments
-    /// * pos - ブロックの座標
-    ///
-    /// # Panics
-    /// * 指定された座標が見つからない場合
-    pub fn insert(&mut self, pos: Vec2) {
-        let map = self.0;
-        for y in 0..map.len() {
-            for x in 0..map[0].len() {
-                // ループから値に対応したXY座標を取得
-                let current_pos = Vec2::new(
-                    FIELD_LEFT_TOP.x + GRID_SIZE * x as f32, 
-                    FIELD_LEFT_TOP.y + GRID_SIZE * 4.0 - GRID_SIZE * y as f32,
-                );
-                // そのXY座標と渡された値が一致したら、マップに値を追加
-                if current_pos == pos {
-                    return self.0[y][x] = 1;
-                }
-            }
+    /// 現在の不透明度（0.0から1.0）を返すメソッド
+    pub fn alpha(&self) -> f32 {
+        let faded = self.elapsed - self.delay;
+        if self.fade <= 0.0 {
+            if faded >= 0.0 { 0.0 } else { 1.0 }
+        } else {
+            1.0 - (faded / self.fade).clamp(0.0, 1.0)
         }
-        // 値は必ず代入されなければならない
-        panic!("pos no found: {}", pos);
     }
 
-    /// 渡された削除するブロックの列のIDを参照して
-    /// 消されるブロックをブロックマップに更新するメソッド
-    ///
-    /// # Arguments
-    /// * index - 削除するブロックの列のID
-    pub fn clearline(&mut self, index: usize) {
-        let map = self.0;
-        // 渡された値の行を全て0にする
-        self.0[index] = [0; 10];
-        // 0にした行から上の値を一段下にずらす
-        for i in (1..=index).rev() {
-            self.0[i] = map[i - 1];
-        }
+    /// 指定した秒数の間、ブロックを再表示するメソッド
+    pub fn reveal(&mut self, seconds: f32) {
+        self.elapsed = self.elapsed.min(self.delay - seconds);
+    }
+}
+
+/// フィールド内のブロックの配置を管理するリソース
+/// ブロックの衝突判定やライン削除は全てこの盤面を元に行われ、
+/// ブロックのエンティティは盤面の状態を描画するだけになる
+#[derive(Resource, Debug, Deref, DerefMut)]
+pub struct BlockMap(pub Board);
+
+impl BlockMap {
+    pub fn new(layout: &FieldLayout) -> Self {
+        Self(Board::new(layout.width, layout.board_height()))
     }
 }
 
+/// ブロックに対して最後に成功した操作
+/// Tスピンの判定に使用される
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BlockAction {
+    Spawn,
+    Move,
+    Rotate,
+}
+
 /// 現在動かしているブロックを管理するリソース
-/// idには[usize; 16]で定義されているindexが格納される
-/// posには回転時に軸となるXYZ軸が定義される
+/// blockidには回転状態（ブロックデータのindex）が格納される
+/// posには形状の左上のセル座標が格納される
+/// blockdataには回転ルールから取得した4回転分の形状が格納される
+/// last_actionには最後に成功した操作が格納される
+/// kickには最後の回転で使用した壁蹴りのindexが格納される
 #[derive(Resource)]
 pub struct CurrentBlocks {
     pub blocktype: BlockType,
     pub blockid: usize,
-    pub pos: Vec3,
+    pub pos: IVec2,
+    pub blockdata: [Shape; MAX_BLOCK_COUNT],
+    pub last_action: BlockAction,
+    pub kick: usize,
 }
 
 impl CurrentBlocks {
     // リソースを初期化
     pub fn new() -> Self {
         CurrentBlocks {
-            blocktype: BlockType::TypeI,
+            blocktype: BlockType::default(),
             blockid: 0,
-            pos: BLOCK_POSITION,
+            pos: IVec2::ZERO,
+            blockdata: Default::default(),
+            last_action: BlockAction::Spawn,
+            kick: 0,
+        }
+    }
+
+    /// 回転状態と位置から、ブロックが占める盤面のセル座標を返すメソッド
+    /// 戻り値の配列はブロックのIDの順に並ぶ
+    ///
+    /// # Arguments
+    /// * blockid - ブロックの回転状態
+    /// * pos - 形状の左上のセル座標
+    pub fn cells_at(&self, blockid: usize, pos: IVec2) -> Vec<IVec2> {
+        self.blockdata[blockid]
+            .iter()
+            .map(|cell| pos + *cell)
+            .collect()
+    }
+
+    /// 現在のブロックが占める盤面のセル座標を返すメソッド
+    pub fn cells(&self) -> Vec<IVec2> {
+        self.cells_at(self.blockid, self.pos)
+    }
+
+    /// 現在のブロックが衝突せずに下へ移動できる距離を返すメソッド
+    pub fn drop_distance(&self, board: &Board) -> i32 {
+        board.drop_distance(&self.cells())
+    }
+
+    /// 現在のブロックを接地するまで一瞬で落下させるメソッド
+    /// 1マス以上落下した場合は、最後の操作を移動として扱う
+    /// 落下した距離を返す
+    pub fn sonic_drop(&mut self, board: &Board) -> i32 {
+        let distance = self.drop_distance(board);
+        self.pos.y += distance;
+        if distance > 0 {
+            self.last_action = BlockAction::Move;
         }
+        distance
     }
 
-    /// 渡されたブロックIDの回転後のブロックの位置を返すメソッド
+    /// 渡されたブロックIDのブロックの位置を返すメソッド
     ///
     /// # Arguments
-    /// * id - 回転後のブロックの位置を取得するためのブロックID
+    /// * id - ブロックの位置を取得するためのブロックID
+    /// * layout - フィールドの大きさと配置
     ///
     /// # Returns
-    /// * Vec3 - 回転後のブロックの位置
+    /// * Vec3 - フィールド上のブロックの位置
     ///
     /// # Panics
     /// * idが見つからない場合
-    pub fn position(&self, id: usize) -> Vec3 {
-        let blockdata = self.blocktype.blockdata();
-
+    pub fn position(&self, id: usize, layout: &FieldLayout) -> Vec3 {
+        let cells = self.cells();
         // ブロックIDが有効範囲内かチェック
-        assert!(self.blockid < blockdata.len());
-        // 回転後のブロックの位置を見つける
-        for (index, value) in blockdata[self.blockid].iter().enumerate() {
-            if id == *value {
-                // ブロックの新しい位置を計算して返す
-                let (x, y, z) = (
-                    self.pos.x + GRID_SIZE * ((index % 4) as f32),
-                    self.pos.y - GRID_SIZE * ((index / 4) as f32),
-                    self.pos.z,
-                );
-                return Vec3::new(x, y, z);
-            }
+        assert!((1..=cells.len()).contains(&id), "id not found: {}", id);
+        layout.cell_to_translation(cells[id - 1], BLOCK_DEPTH)
+    }
+}
+
+/// ブロックの回転ルールを管理するリソース
+/// モードに合わせて`RotationKind`から生成した実装に差し替えて使用する
+#[derive(Resource, Deref)]
+pub struct BlockRotationSystem(pub Box<dyn RotationSystem>);
+
+impl BlockRotationSystem {
+    pub fn new(kind: RotationKind) -> Self {
+        Self(kind.system())
+    }
+}
+
+/// 次に出現するブロックを生成するランダマイザを管理するリソース
+/// モードに合わせて`GeneratorKind`を差し替えて使用する
+/// ゲーム開始時にはシード値とブロックセットから同じ種類のランダマイザを作り直す
+#[derive(Resource)]
+pub struct BlockGenerator {
+    pub kind: GeneratorKind,
+    pub generator: Box<dyn PieceGenerator>,
+}
+
+impl BlockGenerator {
+    pub fn new(kind: GeneratorKind, seed: u64, pieces: &PieceSet) -> Self {
+        Self {
+            kind,
+            generator: kind.generator(seed, pieces),
         }
-        // ブロックIDは見つからなければならない
-        panic!("id not found: {}", id);
     }
 }
 
+/// ゲームで使用するブロックセットを管理するリソース
+/// アセットを読み込むまでは組み込みの7種類のブロックを使用する
+#[derive(Resource, Deref)]
+pub struct BlockPieces(pub PieceSet);
+
+impl BlockPieces {
+    pub fn new() -> Self {
+        Self(PieceSet::standard())
+    }
+}
+
+/// ルールで指定されたブロックセットのアセットを管理するリソース
+/// `None`の場合は組み込みの7種類のブロックを使用する
+#[derive(Resource, Default, Deref)]
+pub struct PieceSetHandle(pub Option<Handle<PieceSet>>);
+
 /// ホールドされたブロックを管理するリソース
 /// - can_hold: ホールドが可能かどうか判定
 /// - blocktype: ホールドされたブロックの形
@@ -154,17 +275,24 @@ impl HoldBlocks {
 /// 次に生成するブロックを管理するリソース
 /// 値は[BlockType; NEXT_BLOCK_COUNT]で定義されており
 /// 値にはランダムなブロックの形が格納されている
+/// 先頭はフィールドに出ているブロックで、2つ目以降が次に生成するブロックになる
 #[derive(Resource, Debug, Deref, DerefMut)]
 pub struct NextBlocks(pub [BlockType; NEXT_BLOCK_COUNT]);
 
 impl NextBlocks {
     pub fn new() -> Self {
-        let blocktypes = std::array::from_fn(|_| BlockType::TypeI);
+        let blocktypes = std::array::from_fn(|_| BlockType::default());
 
         Self(blocktypes)
     }
 
-    pub fn update(&self, blocktype: BlockType) -> Self {
+    /// ランダマイザから次に生成するブロックを全て埋めるメソッド
+    pub fn fill(generator: &mut dyn PieceGenerator) -> Self {
+        Self(std::array::from_fn(|_| generator.next()))
+    }
+
+    /// ブロックを1つ進め、最後にランダマイザから生成したブロックを追加するメソッド
+    pub fn update(&self, generator: &mut dyn PieceGenerator) -> Self {
         let mut blocktypes = self.0;
 
         // 配列の長さを保証
@@ -173,13 +301,122 @@ impl NextBlocks {
         // 配列を1つ左にシフト
         blocktypes.copy_within(1.., 0);
 
-        // 最後の要素をランダム値で更新
-        blocktypes[NEXT_BLOCK_COUNT - 1] = blocktype;
+        // 最後の要素をランダマイザで生成したブロックで更新
+        blocktypes[NEXT_BLOCK_COUNT - 1] = generator.next();
 
         Self(blocktypes)
     }
 }
 
+/// 連続消去のボーナスを管理するリソース
+/// - b2b: 直前のライン消去が難しい消去（テトリス、Tスピン）だったか
+/// - combo: 連続でラインを消去した回数（直前にライン消去がなければ`None`）
+#[derive(Resource, Default, Debug)]
+pub struct ScoreChain {
+    pub b2b: bool,
+    pub combo: Option<usize>,
+}
+
+impl ScoreChain {
+    /// ブロック固定時に連続消去の状態を更新するメソッド
+    ///
+    /// # Arguments
+    /// * count - 消去したライン数
+    /// * difficult - 難しい消去（テトリス、Tスピン）かどうか
+    ///
+    /// # Returns
+    /// * bool - Back-to-Backのボーナス対象かどうか
+    /// * usize - 連続でラインを消去した回数（初回は0）
+    pub fn update(&mut self, count: usize, difficult: bool) -> (bool, usize) {
+        // ラインを消去しなければコンボが途切れる
+        if count == 0 {
+            self.combo = None;
+            return (false, 0);
+        }
+
+        let combo = self.combo.map_or(0, |combo| combo + 1);
+        let b2b = difficult && self.b2b;
+        self.combo = Some(combo);
+        self.b2b = difficult;
+        (b2b, combo)
+    }
+}
+
+/// せり上がりを待っているおじゃまブロックを管理するリソース
+/// ブロックがラインを消去せずに固定された時に、盤面の下から全てせり上がる
+/// - pending: せり上がりを待っている行数（攻撃ごと）
+/// - rng: 穴の列を決める乱数生成器（ゲーム開始時にシード値から作り直す）
+#[derive(Resource)]
+pub struct GarbageQueue {
+    pub pending: VecDeque<usize>,
+    rng: ChaCha8Rng,
+}
+
+impl GarbageQueue {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            pending: VecDeque::new(),
+            rng: ChaCha8Rng::seed_from_u64(seed),
+        }
+    }
+
+    /// おじゃまブロックをせり上がりの待ちに追加するメソッド
+    pub fn push(&mut self, lines: usize) {
+        if lines > 0 {
+            self.pending.push_back(lines);
+        }
+    }
+
+    /// 攻撃のライン数だけ、せり上がりを待っているおじゃまブロックを先に追加されたものから相殺するメソッド
+    /// 相殺しきれずに残った攻撃のライン数を返す
+    pub fn cancel(&mut self, mut lines: usize) -> usize {
+        while let Some(pending) = self.pending.front_mut() {
+            if lines == 0 {
+                break;
+            }
+            let canceled = lines.min(*pending);
+            *pending -= canceled;
+            lines -= canceled;
+            if *pending == 0 {
+                self.pending.pop_front();
+            }
+        }
+        lines
+    }
+
+    /// せり上がりを待っている行数の合計を返すメソッド
+    pub fn total(&self) -> usize {
+        self.pending.iter().sum()
+    }
+
+    /// せり上がりを待っているおじゃまブロックを全て取り出し、盤面に追加する行を生成するメソッド
+    /// 先に追加された攻撃ほど上の行になる
+    /// 攻撃ごとに穴の列を選び直し、攻撃の中では穴の空き方に従って穴の列を変える
+    ///
+    /// # Arguments
+    /// * width - 盤面の横幅
+    /// * style - 穴の空き方
+    pub fn take_rows(&mut self, width: usize, style: GarbageStyle) -> Vec<Vec<usize>> {
+        let mut rows = Vec::new();
+        if width == 0 {
+            self.pending.clear();
+            return rows;
+        }
+
+        while let Some(lines) = self.pending.pop_front() {
+            let mut hole = self.rng.random_range(0..width);
+            for line in 0..lines {
+                // 2行目以降は確率で前の行と違う列に穴を空ける
+                if line > 0 && width > 1 && self.rng.random_bool(style.change_chance()) {
+                    hole = (hole + self.rng.random_range(1..width)) % width;
+                }
+                rows.push(style.row(width, hole, &mut self.rng));
+            }
+        }
+        rows
+    }
+}
+
 /// ブロックが落下する速度を管理するリソース
 /// タイマーが早くなればなるほどブロックが落下する速度も早くなる
 #[derive(Resource, Deref, DerefMut)]
@@ -191,6 +428,126 @@ impl FallingTimer {
     }
 }
 
+/// おじゃまブロックが時間でせり上がる間隔を管理するリソース
+/// せり上がるたびに間隔が短くなる
+#[derive(Resource, Deref, DerefMut)]
+pub struct GarbageTimer(pub Timer);
+
+impl GarbageTimer {
+    pub fn new(seconds: f32) -> Self {
+        Self(Timer::from_seconds(seconds, TimerMode::Repeating))
+    }
+
+    /// せり上がりの間隔を短くするメソッド
+    /// 間隔は`GARBAGE_TIMER_MIN_INTERVAL`より短くならない
+    pub fn accelerate(&mut self) {
+        let seconds = (self.duration().as_secs_f32() * GARBAGE_TIMER_ACCELERATION)
+            .max(GARBAGE_TIMER_MIN_INTERVAL);
+        self.set_duration(Duration::from_secs_f32(seconds));
+    }
+}
+
+/// 現在の重力（1フレームあたりに落下するマス数）を管理するリソース
+/// 20Gに達している場合、ブロックは生成や移動、回転の直後に接地する
+#[derive(Resource, Default, Debug, Deref, DerefMut)]
+pub struct Gravity(pub f32);
+
+impl Gravity {
+    /// ブロックが一瞬で接地する重力かどうか判定するメソッド
+    pub fn is_instant(&self) -> bool {
+        self.0 >= MAX_GRAVITY
+    }
+}
+
+/// 出現待ち（ARE）やライン消去待ちの時間を管理するリソース
+/// フェーズに入るたびにルールの秒数で初期化される
+#[derive(Resource, Deref, DerefMut)]
+pub struct PhaseTimer(pub Timer);
+
+impl PhaseTimer {
+    pub fn new(seconds: f32) -> Self {
+        Self(Timer::from_seconds(seconds, TimerMode::Once))
+    }
+}
+
+/// 出現待ちの間に押されていたキーを管理するリソース（IRS、IHS）
+/// - rotation: 出現するブロックに適用する回転の方向
+/// - hold: 出現するブロックをすぐにホールドするか
+#[derive(Resource, Default, Debug)]
+pub struct InitialActions {
+    pub rotation: Option<Direction>,
+    pub hold: bool,
+}
+
+/// 消去待ちの行を管理するリソース
+/// 盤面からは固定時に削除され、見た目のブロックは消去待ちが終わってから詰められる
+#[derive(Resource, Default, Debug, Deref, DerefMut)]
+pub struct ClearingRows(pub Vec<usize>);
+
+/// ブロックが接地してから固定されるまでを管理するリソース
+/// - timer: 接地している間だけ進み、終わるとブロックが固定される
+/// - resets: 接地中に移動や回転でタイマーをリセットした回数
+/// - lowest: これまでにブロックが到達した一番下の行
+#[derive(Resource)]
+pub struct LockDelay {
+    pub timer: Timer,
+    pub resets: usize,
+    pub lowest: i32,
+}
+
+impl LockDelay {
+    pub fn new(seconds: f32) -> Self {
+        Self {
+            timer: Timer::from_seconds(seconds, TimerMode::Once),
+            resets: 0,
+            lowest: 0,
+        }
+    }
+
+    /// ブロックの移動や回転に成功した時にタイマーをリセットするメソッド
+    /// 一番下の行を更新した場合はリセット回数も元に戻し、
+    /// そうでない場合はリセット回数が上限に達するまでリセットする
+    ///
+    /// # Arguments
+    /// * y - 移動後のブロックの行
+    /// * limit - リセットできる回数の上限
+    pub fn reset(&mut self, y: i32, limit: usize) {
+        if y > self.lowest {
+            self.lowest = y;
+            self.resets = 0;
+            self.timer.reset();
+        } else if self.resets < limit {
+            self.resets += 1;
+            self.timer.reset();
+        }
+    }
+}
+
+/// ブロックの操作感を管理する設定リソース
+/// - das: キーを押し続けてから左右の連続移動が始まるまでの秒数
+/// - arr: 連続移動の間隔の秒数（0なら壁まで一瞬で移動する）
+/// - das_cut: 回転した後に左右の連続移動を止める秒数
+/// - soft_drop_factor: ソフトドロップ時に落下速度を何倍にするか
+///   （`f32::INFINITY`なら一瞬で接地する）
+#[derive(Resource, Clone, Debug)]
+pub struct Handling {
+    pub das: f32,
+    pub arr: f32,
+    pub das_cut: f32,
+    pub soft_drop_factor: f32,
+}
+
+impl Default for Handling {
+    fn default() -> Self {
+        Self {
+            das: BLOCK_DAS,
+            arr: BLOCK_ARR,
+            das_cut: BLOCK_DAS_CUT,
+            soft_drop_factor: SOFT_DROP_FACTOR,
+        }
+    }
+}
+
 /// ブロックが左に移動する速度を管理するリソース
 #[derive(Resource, Deref, DerefMut)]
 pub struct MoveLeftTimer(pub Stopwatch);
@@ -215,36 +572,97 @@ fn despawn(
     }
 }
 
+/// ルールで指定されたブロックセットのアセットを読み込む関数
+/// ルールが変更されるたびに実行される
+fn load_pieceset(
+    mut handle: ResMut<PieceSetHandle>,
+    asset_server: Res<AssetServer>,
+    ruleset: Res<Ruleset>,
+) {
+    info_once!("load_pieceset");
+
+    // 読み込み済みのアセットであれば、同じハンドルが返される
+    *handle = PieceSetHandle(ruleset.piece_set.map(|path| asset_server.load(path)));
+}
+
+/// 読み込んだブロックセットをゲームに反映する関数
+/// ゲームの途中でブロックの種類が変わらないよう、ゲーム中以外に実行される
+fn apply_pieceset(
+    mut pieces: ResMut<BlockPieces>,
+    handle: Res<PieceSetHandle>,
+    assets: Res<Assets<PieceSet>>,
+) {
+    info_once!("apply_pieceset");
+
+    if !handle.is_changed() && !assets.is_changed() {
+        return;
+    }
+
+    let pieceset = match &handle.0 {
+        // 読み込みが終わっていなければ、今のブロックセットを使い続ける
+        Some(handle) => match assets.get(handle) {
+            Some(pieceset) => pieceset.clone(),
+            None => return,
+        },
+        None => PieceSet::standard(),
+    };
+    debug!("piece set: {}", pieceset.name);
+    *pieces = BlockPieces(pieceset);
+}
+
 /// リソースをセットアップする関数
+#[allow(clippy::too_many_arguments)]
 fn setup(
     mut commands: Commands,
     mut _currentblock: ResMut<CurrentBlocks>,
-    mut _blockmap: ResMut<BlockMap>,
-    mut blockrandomizer: ResMut<BlockRandomizer>,
-    mut _holdblocks: ResMut<HoldBlocks>,
+    mut blockmap: ResMut<BlockMap>,
+    mut blockgenerator: ResMut<BlockGenerator>,
+    mut holdblocks: ResMut<HoldBlocks>,
     mut nextblocks: ResMut<NextBlocks>,
+    mut garbage: ResMut<GarbageQueue>,
+    mut seed: ResMut<Seed>,
+    ruleset: Res<Ruleset>,
+    pieces: Res<BlockPieces>,
 ) {
     info_once!("setup");
 
-    **nextblocks = std::array::from_fn(|_| blockrandomizer.next().unwrap());
+    // ルールで決められた大きさの盤面を生成
+    *blockmap = BlockMap::new(&ruleset.layout());
+
+    // ホールドを使えないルールでは、最初からホールドできないようにする
+    holdblocks.can_hold = ruleset.hold;
+
+    // シード値からランダマイザを作り直す
+    let seed = seed.roll();
+    debug!("seed: {}", seed);
+    *blockgenerator = BlockGenerator::new(blockgenerator.kind, seed, &pieces);
+    *garbage = GarbageQueue::new(seed);
+
+    // ルールで決められた行数のおじゃまブロックを盤面の下に並べる
+    if ruleset.start_garbage > 0 {
+        garbage.push(ruleset.start_garbage);
+        commands.trigger(GarbageRaised);
+    }
+
+    *nextblocks = NextBlocks::fill(blockgenerator.generator.as_mut());
     commands.trigger(BlockSpawned(Some(nextblocks[0])));
 }
 
 /// リソースをリセットする関数
 fn reset(
     mut currentblock: ResMut<CurrentBlocks>,
-    mut blockmap: ResMut<BlockMap>,
-    mut blockrandomizer: ResMut<BlockRandomizer>,
     mut holdblocks: ResMut<HoldBlocks>,
     mut nextblocks: ResMut<NextBlocks>,
+    mut scorechain: ResMut<ScoreChain>,
+    mut clearingrows: ResMut<ClearingRows>,
 ) {
     info_once!("reset");
 
     *currentblock = CurrentBlocks::new();
-    *blockmap = BlockMap(BLOCK_MAP);
-    *blockrandomizer = BlockRandomizer::new();
     *holdblocks = HoldBlocks::new();
     *nextblocks = NextBlocks::new();
+    *scorechain = ScoreChain::default();
+    clearingrows.clear();
 }
 
 pub struct UtilsPlugin;
@@ -253,17 +671,87 @@ impl Plugin for UtilsPlugin {
     fn build(&self, app: &mut App) {
         app
             .insert_resource(CurrentBlocks::new())
-            .insert_resource(BlockMap(BLOCK_MAP))
-            .insert_resource(BlockRandomizer::new())
+            .insert_resource(BlockMap::new(&Ruleset::default().layout()))
+            .insert_resource(BlockRotationSystem::new(RotationKind::default()))
+            .insert_resource(BlockGenerator::new(GeneratorKind::default(), 0, &PieceSet::standard()))
+            .insert_resource(BlockPieces::new())
+            .insert_resource(PieceSetHandle::default())
             .insert_resource(HoldBlocks::new())
             .insert_resource(NextBlocks::new())
+            .insert_resource(ScoreChain::default())
+            .insert_resource(GarbageQueue::new(0))
             .insert_resource(FallingTimer::new())
+            .insert_resource(GarbageTimer::new(GARBAGE_TIMER_MIN_INTERVAL))
+            .insert_resource(Gravity::default())
+            .insert_resource(Ruleset::default())
+            .insert_resource(LockDelay::new(BLOCK_LOCK_DELAY))
+            .insert_resource(PhaseTimer::new(BLOCK_ARE))
+            .insert_resource(ClearingRows::default())
+            .insert_resource(InitialActions::default())
+            .insert_resource(Handling::default())
             .insert_resource(MoveLeftTimer(Stopwatch::new()))
             .insert_resource(MoveRightTimer(Stopwatch::new()))
             .insert_resource(MoveBottomTimer(Stopwatch::new()))
+            .init_asset::<PieceSet>()
+            .init_asset_loader::<PieceSetLoader>()
+            .add_systems(Update, (
+                load_pieceset.run_if(resource_changed::<Ruleset>),
+                apply_pieceset.run_if(not(in_state(AppState::InGame))),
+            ).chain())
             .add_systems(OnExit(AppState::Gameover), despawn)
             .add_systems(OnEnter(AppState::InGame), setup)
             .add_systems(OnExit(AppState::Gameover), reset)
          ;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn score_chain_tracks_b2b_and_combo() {
+        let mut chain = ScoreChain::default();
+        let table = [
+            // (消去したライン数, 難しい消去か, B2B, コンボ数)
+            (4, true, false, 0),
+            (4, true, true, 1),
+            // ラインを消さなければコンボは途切れるが、B2Bは続く
+            (0, false, false, 0),
+            (2, true, true, 0),
+            // 難しくない消去でB2Bが途切れる
+            (1, false, false, 1),
+            (4, true, false, 2),
+        ];
+        for (step, (count, difficult, b2b, combo)) in table.into_iter().enumerate() {
+            assert_eq!(chain.update(count, difficult), (b2b, combo), "step {}", step);
+        }
+    }
+
+    #[test]
+    fn garbage_styles_fill_rows() {
+        let width = 10;
+        for style in [GarbageStyle::Clean, GarbageStyle::Messy, GarbageStyle::Cheese, GarbageStyle::Scattered] {
+            let mut garbage = GarbageQueue::new(1);
+            garbage.push(12);
+            let rows = garbage.take_rows(width, style);
+            assert_eq!(rows.len(), 12, "{:?}", style);
+            assert!(garbage.pending.is_empty(), "{:?}", style);
+            for row in &rows {
+                let holes = row.iter().filter(|cell| **cell == 0).count();
+                assert!(holes >= 1, "{:?}", style);
+                if style != GarbageStyle::Scattered {
+                    assert_eq!(holes, 1, "{:?}", style);
+                }
+            }
+            let hole = |row: &Vec<usize>| row.iter().position(|cell| *cell == 0);
+            match style {
+                GarbageStyle::Clean => assert!(rows.iter().all(|row| hole(row) == hole(&rows[0]))),
+                GarbageStyle::Cheese => assert!(rows.windows(2).all(|pair| hole(&pair[0]) != hole(&pair[1]))),
+                // NES版のBタイプのように、1行に複数の穴が空く
+                GarbageStyle::Scattered => assert!(rows.iter().any(|row| row.iter().filter(|cell| **cell == 0).count() > 1)),
+                GarbageStyle::Messy => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,413 @@
+use rand::prelude::*;
+use rand_chacha::ChaCha8Rng;
+use std::collections::VecDeque;
+
+use crate::ingame::BlockType;
+use super::pieceset::PieceSet;
+
+const TGM3_POOL_REPEAT: usize = 5;
+const TGM3_ROLL_COUNT: usize = 6;
+const TGM_HISTORY_COUNT: usize = 4;
+const TGM1_ROLL_COUNT: usize = 4;
+const TGM2_ROLL_COUNT: usize = 6;
+
+/// 次に出現するブロックを生成するトレイト
+/// モードごとに実装を切り替えることで、ブロックの出現順を変更できる
+pub trait PieceGenerator: Send + Sync {
+    /// 次に出現するブロックを返すメソッド
+    fn next(&mut self) -> BlockType;
+}
+
+/// ランダマイザの種類
+/// マスターとクラシック以外のモードでは、メインメニューで選択できる
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum GeneratorKind {
+    /// 全種類を1つずつ1セットとしてシャッフルする（ガイドライン準拠）
+    Bag7,
+    /// 全種類を2つずつ1セットとしてシャッフルする
+    Bag14,
+    /// 履歴4つ、4回まで引き直す（TGM準拠）
+    Tgm1,
+    /// 履歴4つ、6回まで引き直す（TGM2準拠）
+    Tgm2,
+    /// 種類数x5個のプールと履歴4つ、6回まで引き直す（TGM3準拠）
+    #[default]
+    Tgm3,
+    /// 直前と同じブロックなら1回だけ引き直す（NES版テトリス準拠）
+    Nes,
+    /// 完全にランダム
+    Random,
+}
+
+impl GeneratorKind {
+    /// メインメニューで選択できる順番に並べた全ての種類
+    pub const ALL: [GeneratorKind; 7] = [
+        GeneratorKind::Bag7,
+        GeneratorKind::Bag14,
+        GeneratorKind::Tgm1,
+        GeneratorKind::Tgm2,
+        GeneratorKind::Tgm3,
+        GeneratorKind::Nes,
+        GeneratorKind::Random,
+    ];
+
+    /// ランダマイザの実装を生成するメソッド
+    /// 同じシード値とブロックセットからは必ず同じ順番でブロックが生成される
+    /// リプレイや同じ順番での対戦のため、環境やrandのバージョンで結果が変わらない乱数生成器を使う
+    pub fn generator(&self, seed: u64, pieces: &PieceSet) -> Box<dyn PieceGenerator> {
+        let rng = ChaCha8Rng::seed_from_u64(seed);
+        let candidates = Candidates::new(pieces);
+        match self {
+            GeneratorKind::Bag7 => Box::new(Bag::new(rng, candidates, 1)),
+            GeneratorKind::Bag14 => Box::new(Bag::new(rng, candidates, 2)),
+            GeneratorKind::Tgm1 => Box::new(History::new(rng, candidates, TGM1_ROLL_COUNT,
+                initial_history(pieces, ["Z", "Z", "Z", "Z"]),
+            )),
+            GeneratorKind::Tgm2 => Box::new(History::new(rng, candidates, TGM2_ROLL_COUNT,
+                initial_history(pieces, ["Z", "S", "S", "Z"]),
+            )),
+            GeneratorKind::Tgm3 => Box::new(Tgm3::new(rng, candidates,
+                initial_history(pieces, ["S", "Z", "S"]),
+            )),
+            GeneratorKind::Nes => Box::new(Nes::new(rng, candidates)),
+            GeneratorKind::Random => Box::new(Random(rng, candidates)),
+        }
+    }
+}
+
+/// 履歴の初期値をブロックの名前から作る関数
+/// ブロックセットに含まれない名前は無視される
+fn initial_history<const N: usize>(pieces: &PieceSet, names: [&str; N]) -> VecDeque<BlockType> {
+    names
+        .iter()
+        .filter_map(|name| pieces.find(name))
+        .collect()
+}
+
+/// ランダマイザが選ぶブロックの候補
+/// - all: ブロックセットの全種類のブロック
+/// - first: 一番初めに生成されるブロックの候補
+pub struct Candidates {
+    all: Vec<BlockType>,
+    first: Vec<BlockType>,
+}
+
+impl Candidates {
+    pub fn new(pieces: &PieceSet) -> Self {
+        Self {
+            all: pieces.all(),
+            first: pieces.first.clone(),
+        }
+    }
+
+    /// 一番初めに生成されるブロックを候補からランダムに選ぶメソッド
+    fn first_piece(&self, rng: &mut ChaCha8Rng) -> BlockType {
+        match self.first.choose(rng) {
+            Some(piece) => *piece,
+            None => self.random_piece(rng),
+        }
+    }
+
+    /// 全種類のブロックから1つをランダムに選ぶメソッド
+    fn random_piece(&self, rng: &mut ChaCha8Rng) -> BlockType {
+        *self.all
+            .choose(rng)
+            .expect("piece set should not be empty")
+    }
+}
+
+/// 全種類のブロックを決まった数ずつ袋に入れ、シャッフルして順番に取り出すランダマイザ
+/// - rng: シード値から生成した乱数生成器
+/// - candidates: ブロックの候補
+/// - copies: 袋に入れる各ブロックの数
+/// - bag: 袋に残っているブロック
+pub struct Bag {
+    rng: ChaCha8Rng,
+    candidates: Candidates,
+    copies: usize,
+    bag: Vec<BlockType>,
+}
+
+impl Bag {
+    pub fn new(rng: ChaCha8Rng, candidates: Candidates, copies: usize) -> Self {
+        Self {
+            rng,
+            candidates,
+            copies,
+            bag: Vec::new(),
+        }
+    }
+}
+
+impl PieceGenerator for Bag {
+    fn next(&mut self) -> BlockType {
+        // 袋が空になったら補充してシャッフル
+        if self.bag.is_empty() {
+            for _ in 0..self.copies {
+                self.bag.extend(&self.candidates.all);
+            }
+            self.bag.shuffle(&mut self.rng);
+        }
+        self.bag.pop().expect("bag should not be empty")
+    }
+}
+
+/// 直近に出たブロックの履歴を使い、決まった回数まで引き直すランダマイザ（TGM、TGM2）
+/// - rng: シード値から生成した乱数生成器
+/// - candidates: ブロックの候補
+/// - rolls: 履歴にないブロックを探して引く回数
+/// - history: 直近で出たブロック
+/// - first: 最初だけ特別な動作をするフラグ
+pub struct History {
+    rng: ChaCha8Rng,
+    candidates: Candidates,
+    rolls: usize,
+    history: VecDeque<BlockType>,
+    first: bool,
+}
+
+impl History {
+    pub fn new(rng: ChaCha8Rng, candidates: Candidates, rolls: usize, history: VecDeque<BlockType>) -> Self {
+        Self {
+            rng,
+            candidates,
+            rolls,
+            history,
+            first: true,
+        }
+    }
+}
+
+impl PieceGenerator for History {
+    fn next(&mut self) -> BlockType {
+        // 初回は候補から選ぶ
+        let picked_piece = if self.first {
+            self.first = false;
+            self.candidates.first_piece(&mut self.rng)
+        } else {
+            // 履歴にないブロックが出るまで引き直し、最後に引いたものを採用する
+            let mut picked_piece = self.candidates.random_piece(&mut self.rng);
+            for _ in 1..self.rolls {
+                if !self.history.contains(&picked_piece) {
+                    break;
+                }
+                picked_piece = self.candidates.random_piece(&mut self.rng);
+            }
+            picked_piece
+        };
+
+        // historyを更新（先頭を外して末尾に追加）
+        if self.history.len() == TGM_HISTORY_COUNT {
+            self.history.pop_front();
+        }
+        self.history.push_back(picked_piece);
+
+        picked_piece
+    }
+}
+
+/// 種類数x5個のプールと履歴を使い、出ていないブロックほど出やすくするランダマイザ（TGM3）
+/// - rng: シード値から生成した乱数生成器
+/// - order: 直近に出た順序（重複排除付き）
+/// - pool: ピース候補のプール（テトリスブロックなら35個）
+/// - history: 直近で出たブロック（同じブロックの連続防止）
+/// - first: 最初だけ特別な動作をするフラグ
+pub struct Tgm3 {
+    rng: ChaCha8Rng,
+    order: VecDeque<BlockType>,
+    pool: Vec<BlockType>,
+    history: VecDeque<BlockType>,
+    first: bool,
+}
+
+impl Tgm3 {
+    pub fn new(mut rng: ChaCha8Rng, candidates: Candidates, mut history: VecDeque<BlockType>) -> Self {
+        // プールを種類数x5で埋める（公平性確保）
+        let pool = candidates.all.repeat(TGM3_POOL_REPEAT);
+
+        // 履歴を初期化（TGM3準拠：S, Z, S, 最初ブロック）
+        history.push_back(candidates.first_piece(&mut rng));
+
+        Self {
+            rng,
+            order: VecDeque::new(),
+            pool,
+            history,
+            first: true,
+        }
+    }
+}
+
+impl PieceGenerator for Tgm3 {
+    fn next(&mut self) -> BlockType {
+        // 初回だけhistoryの末尾（first_piece）を返す
+        if self.first {
+            self.first = false;
+            if let Some(piece) = self.history.back() {
+                return *piece;
+            }
+        }
+
+        let mut picked_piece = BlockType::default();
+        let mut idx = 0;
+        // 最大6回まで「historyにないブロック」を探す
+        for roll in 0..TGM3_ROLL_COUNT {
+            idx = self.rng.random_range(0..self.pool.len());
+            picked_piece = self.pool[idx];
+            if !self.history.contains(&picked_piece) || roll == TGM3_ROLL_COUNT - 1 {
+                break;
+            }
+            // 既出順のブロックで置き換え
+            if let Some(&first_order) = self.order.front() {
+                self.pool[idx] = first_order;
+            }
+        }
+
+        // orderを更新（重複を削除して追加）
+        if let Some(pos) = self.order.iter().position(|&x| x == picked_piece) {
+            self.order.remove(pos);
+        }
+        self.order.push_back(picked_piece);
+
+        // poolを更新（orderの先頭で置き換え）
+        if let Some(&first_order) = self.order.front() {
+            self.pool[idx] = first_order;
+        }
+
+        // historyを更新（先頭を外して末尾に追加）
+        if self.history.len() == TGM_HISTORY_COUNT {
+            self.history.pop_front();
+        }
+        self.history.push_back(picked_piece);
+
+        picked_piece
+    }
+}
+
+/// 直前と同じブロックが出たら1回だけ引き直すランダマイザ（NES版テトリス）
+/// 種類数+1面のサイコロを振り、最後の目が出た場合も引き直す（テトリスブロックなら8面）
+/// - rng: シード値から生成した乱数生成器
+/// - candidates: ブロックの候補
+/// - previous: 直前に出たブロック
+pub struct Nes {
+    rng: ChaCha8Rng,
+    candidates: Candidates,
+    previous: Option<BlockType>,
+}
+
+impl Nes {
+    pub fn new(rng: ChaCha8Rng, candidates: Candidates) -> Self {
+        Self {
+            rng,
+            candidates,
+            previous: None,
+        }
+    }
+}
+
+impl PieceGenerator for Nes {
+    fn next(&mut self) -> BlockType {
+        let index = self.rng.random_range(0..=self.candidates.all.len());
+        let picked_piece = match self.candidates.all.get(index) {
+            Some(piece) if Some(*piece) != self.previous => *piece,
+            _ => self.candidates.random_piece(&mut self.rng),
+        };
+
+        self.previous = Some(picked_piece);
+        picked_piece
+    }
+}
+
+/// 全種類のブロックから毎回ランダムに選ぶランダマイザ
+pub struct Random(ChaCha8Rng, Candidates);
+
+impl PieceGenerator for Random {
+    fn next(&mut self) -> BlockType {
+        self.1.random_piece(&mut self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// ランダマイザから指定した数のブロックを生成する関数
+    fn sequence(kind: GeneratorKind, seed: u64, count: usize) -> Vec<BlockType> {
+        let mut generator = kind.generator(seed, &PieceSet::standard());
+        (0..count).map(|_| generator.next()).collect()
+    }
+
+    /// ブロックを種類の順に並べ替える関数
+    fn sorted(mut pieces: Vec<BlockType>) -> Vec<BlockType> {
+        pieces.sort_by_key(|piece| piece.0);
+        pieces
+    }
+
+    #[test]
+    fn same_seed_gives_same_sequence() {
+        for kind in GeneratorKind::ALL {
+            assert_eq!(sequence(kind, 42, 200), sequence(kind, 42, 200), "{:?}", kind);
+            assert_ne!(sequence(kind, 42, 200), sequence(kind, 43, 200), "{:?}", kind);
+        }
+    }
+
+    #[test]
+    fn each_bag_is_a_permutation() {
+        let pieces = PieceSet::standard();
+        for (kind, copies) in [(GeneratorKind::Bag7, 1), (GeneratorKind::Bag14, 2)] {
+            let expected = sorted(pieces.all().repeat(copies));
+            let sequence = sequence(kind, 7, expected.len() * 10);
+            for bag in sequence.chunks(expected.len()) {
+                assert_eq!(sorted(bag.to_vec()), expected, "{:?}", kind);
+            }
+        }
+    }
+
+    #[test]
+    fn tgm_first_piece_is_never_s_z_or_o() {
+        let pieces = PieceSet::standard();
+        for kind in [GeneratorKind::Tgm1, GeneratorKind::Tgm2, GeneratorKind::Tgm3] {
+            for seed in 0..200 {
+                let first = sequence(kind, seed, 1)[0];
+                assert!(!["S", "Z", "O"].contains(&pieces.piece(first).name.as_str()), "{:?} seed {}", kind, seed);
+            }
+        }
+    }
+
+    #[test]
+    fn history_rerolls_pieces_in_history() {
+        let pieces = PieceSet::standard();
+        // 引き直しの回数が十分多ければ、直近4つと同じブロックは出ない
+        let mut generator = History::new(
+            ChaCha8Rng::seed_from_u64(0),
+            Candidates::new(&pieces),
+            1000,
+            initial_history(&pieces, ["Z", "Z", "Z", "Z"]),
+        );
+        let sequence: Vec<BlockType> = (0..500).map(|_| generator.next()).collect();
+        for window in sequence.windows(TGM_HISTORY_COUNT + 1) {
+            let (last, history) = window.split_last().unwrap();
+            assert!(!history.contains(last), "{:?}", window);
+        }
+
+        // 1回しか引かなければ履歴に関係なく選ぶので、同じブロックが続くこともある
+        let mut generator = History::new(
+            ChaCha8Rng::seed_from_u64(0),
+            Candidates::new(&pieces),
+            1,
+            VecDeque::new(),
+        );
+        let sequence: Vec<BlockType> = (0..500).map(|_| generator.next()).collect();
+        assert!(sequence.windows(2).any(|pair| pair[0] == pair[1]));
+    }
+
+    #[test]
+    fn tgm_history_starts_with_the_initial_pieces() {
+        let pieces = PieceSet::standard();
+        let history = initial_history(&pieces, ["Z", "S", "S", "Z"]);
+        let names: Vec<&str> = history.iter().map(|piece| pieces.piece(*piece).name.as_str()).collect();
+        assert_eq!(names, ["Z", "S", "S", "Z"]);
+        // ブロックセットにない名前は無視する
+        assert_eq!(initial_history(&pieces, ["Z", "X"]).len(), 1);
+    }
+}
@@ -0,0 +1,634 @@
+use bevy::{
+    asset::{
+        io::Reader,
+        AssetLoader,
+        LoadContext,
+    },
+    prelude::*,
+};
+use serde::Deserialize;
+use std::collections::HashMap;
+use thiserror::Error;
+
+use crate::GRID_SIZE_HALF;
+use super::blockdata::*;
+use super::blocktype::BlockType;
+use super::rotationsystem::RotationKind;
+
+/// ブロック1回転分の形状
+/// ブロックを構成するセルの、形状の左上からの相対座標を持つ
+/// 配列の順番がブロックのID（1から始まる）の順番になる
+pub type Shape = Vec<IVec2>;
+
+/// 回転の方向ごとに、回転前の状態（0, R, 2, L）から試行する壁蹴りのデータ
+/// - right: 右回転時の壁蹴り
+/// - left: 左回転時の壁蹴り
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KickTable {
+    pub right: [Vec<IVec2>; MAX_BLOCK_COUNT],
+    pub left: [Vec<IVec2>; MAX_BLOCK_COUNT],
+}
+
+impl KickTable {
+    /// 壁蹴りを行わない（その場でのみ回転を試す）データ
+    pub fn none() -> Self {
+        Self {
+            right: std::array::from_fn(|_| O_KICKS.to_vec()),
+            left: std::array::from_fn(|_| O_KICKS.to_vec()),
+        }
+    }
+
+    fn from_consts(right: &[[IVec2; 5]; MAX_BLOCK_COUNT], left: &[[IVec2; 5]; MAX_BLOCK_COUNT]) -> Self {
+        Self {
+            right: std::array::from_fn(|i| right[i].to_vec()),
+            left: std::array::from_fn(|i| left[i].to_vec()),
+        }
+    }
+
+    /// 回転前と回転後の状態から、試行する壁蹴りを返すメソッド
+    pub fn kicks(&self, from: usize, to: usize) -> &[IVec2] {
+        if to == (from + 1) % MAX_BLOCK_COUNT {
+            &self.right[from]
+        } else {
+            &self.left[from]
+        }
+    }
+}
+
+/// アリカ・ローテーションシステムで、回転できない時に左右へずらすかどうか
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+pub enum ArsKick {
+    /// 右、左の順に1マスずらして試す
+    #[default]
+    Side,
+    /// 中央列で衝突した場合はずらさず、それ以外は右、左の順に1マスずらして試す（中央列ルール）
+    CenterColumn,
+    /// ずらさない
+    Never,
+}
+
+/// 1種類のブロックの定義
+/// - name: ブロックの名前（ブロックセットの中で重複しない）
+/// - color: ブロックの色
+/// - shapes: 4回転分の形状
+/// - rotation_shapes: 回転ルール独自の4回転分の形状（定義がない回転ルールでは`shapes`を使う）
+/// - kicks: SRSの回転時に試行する壁蹴り
+/// - ars_kick: アリカ・ローテーションシステムの回転時に左右へずらすかどうか
+/// - spin: Tスピンの判定を行うか
+/// - spawn_offset: 出現位置からのずれ（セル単位）
+/// - preview_offset: ホールドやネクストに表示する時のずれ（半マス単位、下向きが正）
+#[derive(Clone, Debug)]
+pub struct PieceData {
+    pub name: String,
+    pub color: Color,
+    pub shapes: [Shape; MAX_BLOCK_COUNT],
+    pub rotation_shapes: HashMap<RotationKind, [Shape; MAX_BLOCK_COUNT]>,
+    pub kicks: KickTable,
+    pub ars_kick: ArsKick,
+    pub spin: bool,
+    pub spawn_offset: IVec2,
+    pub preview_offset: Vec2,
+}
+
+/// ゲームで使用するブロックの種類をまとめたアセット
+/// `BlockType`はこのブロック一覧のindexを表す
+/// - name: ブロックセットの名前
+/// - pieces: ブロックの定義の一覧
+/// - first: ゲーム開始時に出現するブロックの候補
+#[derive(Asset, TypePath, Clone, Debug)]
+pub struct PieceSet {
+    pub name: String,
+    pub pieces: Vec<PieceData>,
+    pub first: Vec<BlockType>,
+}
+
+impl PieceSet {
+    /// 組み込みの7種類のテトリスブロック
+    /// アセットを読み込むまでの間や、読み込みに失敗した場合に使用する
+    pub fn standard() -> Self {
+        let piece = |name: &str, color, grids, kicks, preview_offset| PieceData {
+            name: name.to_string(),
+            color,
+            shapes: shapes_from_grids(grids),
+            rotation_shapes: HashMap::new(),
+            kicks,
+            ars_kick: ArsKick::default(),
+            spin: false,
+            spawn_offset: IVec2::ZERO,
+            preview_offset,
+        };
+        let jlstz = KickTable::from_consts(&JLSTZ_KICKS_RIGHT, &JLSTZ_KICKS_LEFT);
+        let pieces = vec![
+            piece("I", I_COLOR, &I_BLOCK, KickTable::from_consts(&I_KICKS_RIGHT, &I_KICKS_LEFT), Vec2::new(1.0, 1.0)),
+            piece("J", J_COLOR, &J_BLOCK, jlstz.clone(), Vec2::new(1.5, 1.5)),
+            piece("L", L_COLOR, &L_BLOCK, jlstz.clone(), Vec2::new(1.5, 1.5)),
+            piece("O", O_COLOR, &O_BLOCK, KickTable::none(), Vec2::new(1.0, 0.5)),
+            piece("S", S_COLOR, &S_BLOCK, jlstz.clone(), Vec2::new(1.5, 1.5)),
+            piece("T", T_COLOR, &T_BLOCK, jlstz.clone(), Vec2::new(1.5, 1.5)),
+            piece("Z", Z_COLOR, &Z_BLOCK, jlstz, Vec2::new(1.5, 1.5)),
+        ];
+
+        let mut pieceset = Self {
+            name: "Standard".to_string(),
+            pieces,
+            first: Vec::new(),
+        };
+        // 回転ルール独自の形状
+        let rotation_shapes: [(&str, RotationKind, &[[usize; 16]; MAX_BLOCK_COUNT]); 14] = [
+            ("I", RotationKind::Ars, &ARS_I_BLOCK),
+            ("J", RotationKind::Ars, &ARS_J_BLOCK),
+            ("L", RotationKind::Ars, &ARS_L_BLOCK),
+            ("S", RotationKind::Ars, &ARS_S_BLOCK),
+            ("T", RotationKind::Ars, &ARS_T_BLOCK),
+            ("Z", RotationKind::Ars, &ARS_Z_BLOCK),
+            ("I", RotationKind::Nes, &NES_I_BLOCK),
+            ("J", RotationKind::Nes, &NES_J_BLOCK),
+            ("L", RotationKind::Nes, &NES_L_BLOCK),
+            ("S", RotationKind::Nes, &NES_S_BLOCK),
+            ("T", RotationKind::Nes, &NES_T_BLOCK),
+            ("Z", RotationKind::Nes, &NES_Z_BLOCK),
+            ("S", RotationKind::Classic, &CLASSIC_S_BLOCK),
+            ("Z", RotationKind::Classic, &CLASSIC_Z_BLOCK),
+        ];
+        for (name, kind, grids) in rotation_shapes {
+            if let Some(blocktype) = pieceset.find(name) {
+                pieceset.pieces[blocktype.0].rotation_shapes.insert(kind, shapes_from_grids(grids));
+            }
+        }
+        // Iブロックは壁蹴りを行わず、J, L, Tブロックは中央列ルールに従う（TGM準拠）
+        let ars_kicks = [("I", ArsKick::Never), ("J", ArsKick::CenterColumn), ("L", ArsKick::CenterColumn), ("T", ArsKick::CenterColumn)];
+        for (name, ars_kick) in ars_kicks {
+            if let Some(blocktype) = pieceset.find(name) {
+                pieceset.pieces[blocktype.0].ars_kick = ars_kick;
+            }
+        }
+        if let Some(blocktype) = pieceset.find("T") {
+            pieceset.pieces[blocktype.0].spin = true;
+        }
+        // 最初に生成されるであろうブロック（TGM3準拠）
+        pieceset.first = ["I", "J", "L", "T"]
+            .iter()
+            .filter_map(|name| pieceset.find(name))
+            .collect();
+        pieceset
+    }
+
+    /// 全種類のブロックを返すメソッド
+    pub fn all(&self) -> Vec<BlockType> {
+        (0..self.pieces.len()).map(BlockType).collect()
+    }
+
+    /// ブロックの定義を返すメソッド
+    ///
+    /// # Panics
+    /// * ブロックセットに含まれないブロックが渡された場合
+    pub fn piece(&self, blocktype: BlockType) -> &PieceData {
+        &self.pieces[blocktype.0]
+    }
+
+    /// 名前からブロックを探すメソッド
+    pub fn find(&self, name: &str) -> Option<BlockType> {
+        self.pieces
+            .iter()
+            .position(|piece| piece.name == name)
+            .map(BlockType)
+    }
+
+    /// ブロックの色を返すメソッド
+    pub fn color(&self, blocktype: BlockType) -> Color {
+        self.piece(blocktype).color
+    }
+
+    /// ブロックの4回転分の形状を返すメソッド
+    pub fn shapes(&self, blocktype: BlockType) -> &[Shape; MAX_BLOCK_COUNT] {
+        &self.piece(blocktype).shapes
+    }
+
+    /// 回転ルールで使用する4回転分の形状を返すメソッド
+    /// 回転ルール独自の形状が定義されていなければ、ブロックセットの形状を返す
+    pub fn rotation_shapes(&self, blocktype: BlockType, kind: RotationKind) -> &[Shape; MAX_BLOCK_COUNT] {
+        let piece = self.piece(blocktype);
+        piece.rotation_shapes.get(&kind).unwrap_or(&piece.shapes)
+    }
+
+    /// 一番多くのセルを持つブロックのセルの数を返すメソッド
+    /// ホールドやネクストに用意するエンティティの数に使用する
+    pub fn max_units(&self) -> usize {
+        self.pieces
+            .iter()
+            .flat_map(|piece| piece.shapes.iter().map(Vec::len))
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// ホールドやネクストでブロックの描画を始める位置を返すメソッド
+    ///
+    /// # Arguments
+    /// * blocktype - 描画するブロック
+    /// * pos - ボード上で描画を始める基準の位置
+    pub fn preview_position(&self, blocktype: BlockType, pos: Vec3) -> Vec2 {
+        let offset = self.piece(blocktype).preview_offset;
+        Vec2::new(
+            pos.x + GRID_SIZE_HALF * offset.x,
+            pos.y - GRID_SIZE_HALF * offset.y,
+        )
+    }
+}
+
+/// 4x4の形状データ（値はブロックのID）をセルの座標に変換する関数
+fn shape_from_grid(grid: &[usize; 16]) -> Shape {
+    let count = grid.iter().filter(|value| **value != 0).count();
+    let mut cells = vec![IVec2::ZERO; count];
+    for (index, value) in grid.iter().enumerate() {
+        // ブロックデータの値が0であればスキップ
+        if *value == 0 {
+            continue;
+        }
+        cells[*value - 1] = IVec2::new((index % 4) as i32, (index / 4) as i32);
+    }
+    cells
+}
+
+/// 4回転分の形状データをセルの座標に変換する関数
+pub fn shapes_from_grids(grids: &[[usize; 16]; MAX_BLOCK_COUNT]) -> [Shape; MAX_BLOCK_COUNT] {
+    std::array::from_fn(|i| shape_from_grid(&grids[i]))
+}
+
+/// アセットファイルに書かれる壁蹴りデータ
+/// y軸は盤面に合わせて下向きが正になる
+#[derive(Deserialize)]
+struct KickTableDef {
+    right: Vec<Vec<(i32, i32)>>,
+    left: Vec<Vec<(i32, i32)>>,
+}
+
+/// アセットファイルに書かれるブロックの定義
+/// 形状は文字列の行で表し、`.`以外の文字がセルになる
+/// 回転ルール独自の形状は、回転ルールの名前（`Ars`, `Nes`, `Classic`）ごとに定義する
+#[derive(Deserialize)]
+struct PieceDef {
+    name: String,
+    color: (f32, f32, f32),
+    shapes: Vec<Vec<String>>,
+    #[serde(default)]
+    rotation_shapes: HashMap<RotationKind, Vec<Vec<String>>>,
+    #[serde(default)]
+    kicks: Option<String>,
+    #[serde(default)]
+    ars_kick: ArsKick,
+    #[serde(default)]
+    spin: bool,
+    #[serde(default)]
+    spawn_offset: (i32, i32),
+    #[serde(default)]
+    preview_offset: (f32, f32),
+}
+
+/// アセットファイルに書かれるブロックセットの定義
+/// 壁蹴りデータは名前を付けて定義し、各ブロックから名前で参照する
+#[derive(Deserialize)]
+struct PieceSetDef {
+    name: String,
+    #[serde(default)]
+    kicks: HashMap<String, KickTableDef>,
+    pieces: Vec<PieceDef>,
+    #[serde(default)]
+    first: Vec<String>,
+}
+
+/// ブロックセットの読み込み時に発生するエラー
+#[derive(Debug, Error)]
+pub enum PieceSetLoaderError {
+    #[error("Could not load piece set: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Could not parse RON: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+    #[error("Piece set has no pieces")]
+    Empty,
+    #[error("Piece {0} must have {MAX_BLOCK_COUNT} rotations")]
+    RotationCount(String),
+    #[error("Piece {0} must have the same number of cells in every rotation")]
+    CellCount(String),
+    #[error("Kick table {0} must have {MAX_BLOCK_COUNT} rotations in each direction")]
+    KickCount(String),
+    #[error("Kick table {0} is not defined")]
+    UnknownKicks(String),
+    #[error("Piece {0} is not defined")]
+    UnknownPiece(String),
+    #[error("Piece {0} is defined more than once")]
+    DuplicatePiece(String),
+}
+
+impl KickTableDef {
+    fn into_table(self, name: &str) -> Result<KickTable, PieceSetLoaderError> {
+        let convert = |rows: Vec<Vec<(i32, i32)>>| -> Result<[Vec<IVec2>; MAX_BLOCK_COUNT], PieceSetLoaderError> {
+            let rows: Vec<Vec<IVec2>> = rows
+                .into_iter()
+                .map(|row| row.into_iter().map(|(x, y)| IVec2::new(x, y)).collect())
+                .collect();
+            rows.try_into().map_err(|_| PieceSetLoaderError::KickCount(name.to_string()))
+        };
+        Ok(KickTable {
+            right: convert(self.right)?,
+            left: convert(self.left)?,
+        })
+    }
+}
+
+/// 文字列の行で書かれた4回転分の形状を、セルの座標に変換する関数
+/// 上の行から左から順にIDを振る
+fn parse_shapes(name: &str, grids: &[Vec<String>]) -> Result<[Shape; MAX_BLOCK_COUNT], PieceSetLoaderError> {
+    let shapes: Vec<Shape> = grids
+        .iter()
+        .map(|rows| {
+            rows.iter()
+                .enumerate()
+                .flat_map(|(y, row)| {
+                    row.chars()
+                        .enumerate()
+                        .filter(|(_, c)| *c != '.')
+                        .map(move |(x, _)| IVec2::new(x as i32, y as i32))
+                })
+                .collect()
+        })
+        .collect();
+    let shapes: [Shape; MAX_BLOCK_COUNT] = shapes
+        .try_into()
+        .map_err(|_| PieceSetLoaderError::RotationCount(name.to_string()))?;
+    if shapes[0].is_empty() || shapes.iter().any(|shape| shape.len() != shapes[0].len()) {
+        return Err(PieceSetLoaderError::CellCount(name.to_string()));
+    }
+    Ok(shapes)
+}
+
+impl PieceDef {
+    fn into_piece(self, kicks: &HashMap<String, KickTable>) -> Result<PieceData, PieceSetLoaderError> {
+        let shapes = parse_shapes(&self.name, &self.shapes)?;
+        let rotation_shapes = self.rotation_shapes
+            .iter()
+            .map(|(kind, grids)| parse_shapes(&self.name, grids).map(|shapes| (*kind, shapes)))
+            .collect::<Result<HashMap<_, _>, _>>()?;
+
+        let kicks = match self.kicks {
+            Some(name) => kicks
+                .get(&name)
+                .cloned()
+                .ok_or(PieceSetLoaderError::UnknownKicks(name))?,
+            None => KickTable::none(),
+        };
+
+        Ok(PieceData {
+            name: self.name,
+            color: Color::srgb(self.color.0, self.color.1, self.color.2),
+            shapes,
+            rotation_shapes,
+            kicks,
+            ars_kick: self.ars_kick,
+            spin: self.spin,
+            spawn_offset: IVec2::new(self.spawn_offset.0, self.spawn_offset.1),
+            preview_offset: Vec2::new(self.preview_offset.0, self.preview_offset.1),
+        })
+    }
+}
+
+impl TryFrom<PieceSetDef> for PieceSet {
+    type Error = PieceSetLoaderError;
+
+    fn try_from(def: PieceSetDef) -> Result<Self, Self::Error> {
+        let kicks = def.kicks
+            .into_iter()
+            .map(|(name, table)| table.into_table(&name).map(|table| (name, table)))
+            .collect::<Result<HashMap<_, _>, _>>()?;
+        let pieces = def.pieces
+            .into_iter()
+            .map(|piece| piece.into_piece(&kicks))
+            .collect::<Result<Vec<_>, _>>()?;
+        if pieces.is_empty() {
+            return Err(PieceSetLoaderError::Empty);
+        }
+        // 名前で出現の候補を指定するため、同じ名前のブロックは定義できない
+        for (index, piece) in pieces.iter().enumerate() {
+            if pieces[..index].iter().any(|other| other.name == piece.name) {
+                return Err(PieceSetLoaderError::DuplicatePiece(piece.name.clone()));
+            }
+        }
+
+        let mut pieceset = Self {
+            name: def.name,
+            pieces,
+            first: Vec::new(),
+        };
+        // 候補が指定されていなければ全種類から選ぶ
+        pieceset.first = if def.first.is_empty() {
+            pieceset.all()
+        } else {
+            def.first
+                .into_iter()
+                .map(|name| pieceset.find(&name).ok_or(PieceSetLoaderError::UnknownPiece(name)))
+                .collect::<Result<Vec<_>, _>>()?
+        };
+        Ok(pieceset)
+    }
+}
+
+/// RON形式で書かれたブロックセット（`.pieces.ron`）を読み込むローダー
+#[derive(Default)]
+pub struct PieceSetLoader;
+
+impl AssetLoader for PieceSetLoader {
+    type Asset = PieceSet;
+    type Settings = ();
+    type Error = PieceSetLoaderError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let def = ron::de::from_bytes::<PieceSetDef>(&bytes)?;
+        PieceSet::try_from(def)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["pieces.ron"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// RON形式の文字列からブロックセットを読み込む関数
+    fn parse(source: &str) -> Result<PieceSet, PieceSetLoaderError> {
+        PieceSet::try_from(ron::de::from_str::<PieceSetDef>(source)?)
+    }
+
+    /// 4回転とも同じ形の、1種類のブロックだけを持つブロックセットを返す関数
+    fn single_piece(kicks: &str, piece_kicks: &str, first: &str) -> String {
+        format!(r#"(
+            name: "Test",
+            kicks: {{ {kicks} }},
+            pieces: [
+                (
+                    name: "O",
+                    color: (1.0, 1.0, 0.0),
+                    shapes: [["XX"], ["XX"], ["XX"], ["XX"]],
+                    {piece_kicks}
+                ),
+            ],
+            first: [{first}],
+        )"#)
+    }
+
+    #[test]
+    fn parses_small_piece_set() {
+        let pieceset = parse(&single_piece("", "", "")).unwrap();
+        assert_eq!(pieceset.name, "Test");
+        assert_eq!(pieceset.pieces.len(), 1);
+        assert_eq!(pieceset.shapes(BlockType(0))[0], vec![IVec2::new(0, 0), IVec2::new(1, 0)]);
+        assert_eq!(pieceset.piece(BlockType(0)).kicks, KickTable::none());
+        // 候補が指定されていなければ全種類から選ぶ
+        assert_eq!(pieceset.first, vec![BlockType(0)]);
+    }
+
+    #[test]
+    fn rejects_invalid_ron() {
+        assert!(matches!(parse("(name: "), Err(PieceSetLoaderError::Ron(_))));
+    }
+
+    #[test]
+    fn rejects_empty_piece_set() {
+        let result = parse(r#"(name: "Empty", pieces: [])"#);
+        assert!(matches!(result, Err(PieceSetLoaderError::Empty)));
+    }
+
+    #[test]
+    fn rejects_wrong_rotation_count() {
+        let result = parse(r#"(
+            name: "Test",
+            pieces: [(name: "O", color: (1.0, 1.0, 0.0), shapes: [["XX"], ["XX"], ["XX"]])],
+        )"#);
+        assert!(matches!(result, Err(PieceSetLoaderError::RotationCount(name)) if name == "O"));
+    }
+
+    #[test]
+    fn rejects_different_cell_counts() {
+        let result = parse(r#"(
+            name: "Test",
+            pieces: [(name: "O", color: (1.0, 1.0, 0.0), shapes: [["XX"], ["X."], ["XX"], ["XX"]])],
+        )"#);
+        assert!(matches!(result, Err(PieceSetLoaderError::CellCount(name)) if name == "O"));
+    }
+
+    #[test]
+    fn rejects_wrong_kick_count() {
+        let kicks = r#""K": (right: [[(0, 0)], [(0, 0)], [(0, 0)]], left: [[(0, 0)], [(0, 0)], [(0, 0)], [(0, 0)]])"#;
+        let result = parse(&single_piece(kicks, r#"kicks: Some("K"),"#, ""));
+        assert!(matches!(result, Err(PieceSetLoaderError::KickCount(name)) if name == "K"));
+    }
+
+    #[test]
+    fn rejects_unknown_kicks() {
+        let result = parse(&single_piece("", r#"kicks: Some("SRS"),"#, ""));
+        assert!(matches!(result, Err(PieceSetLoaderError::UnknownKicks(name)) if name == "SRS"));
+    }
+
+    #[test]
+    fn rejects_unknown_first_piece() {
+        let result = parse(&single_piece("", "", r#""T""#));
+        assert!(matches!(result, Err(PieceSetLoaderError::UnknownPiece(name)) if name == "T"));
+    }
+
+    #[test]
+    fn rejects_duplicate_piece_names() {
+        let result = parse(r#"(
+            name: "Test",
+            pieces: [
+                (name: "O", color: (1.0, 1.0, 0.0), shapes: [["XX"], ["XX"], ["XX"], ["XX"]]),
+                (name: "O", color: (0.0, 1.0, 1.0), shapes: [["X"], ["X"], ["X"], ["X"]]),
+            ],
+        )"#);
+        assert!(matches!(result, Err(PieceSetLoaderError::DuplicatePiece(name)) if name == "O"));
+    }
+
+    #[test]
+    fn parses_rotation_settings() {
+        let pieceset = parse(r#"(
+            name: "Test",
+            pieces: [
+                (
+                    name: "A",
+                    color: (1.0, 1.0, 0.0),
+                    shapes: [["XX"], ["XX"], ["XX"], ["XX"]],
+                    rotation_shapes: { Nes: [["X", "X"], ["X", "X"], ["X", "X"], ["X", "X"]] },
+                    ars_kick: Never,
+                    spin: true,
+                ),
+            ],
+        )"#).unwrap();
+        let piece = BlockType(0);
+        assert_eq!(pieceset.rotation_shapes(piece, RotationKind::Nes)[0], vec![IVec2::new(0, 0), IVec2::new(0, 1)]);
+        // 定義がない回転ルールでは、ブロックセットの形状を使う
+        assert_eq!(pieceset.rotation_shapes(piece, RotationKind::Ars), pieceset.shapes(piece));
+        assert_eq!(pieceset.piece(piece).ars_kick, ArsKick::Never);
+        assert!(pieceset.piece(piece).spin);
+
+        // 名前が同じでも、定義しなければ回転の設定は引き継がれない
+        let renamed = parse(&single_piece("", "", "")).unwrap();
+        assert_eq!(renamed.piece(piece).ars_kick, ArsKick::Side);
+        assert!(!renamed.piece(piece).spin);
+    }
+
+    #[test]
+    fn rejects_invalid_rotation_shapes() {
+        let result = parse(r#"(
+            name: "Test",
+            pieces: [(
+                name: "O",
+                color: (1.0, 1.0, 0.0),
+                shapes: [["XX"], ["XX"], ["XX"], ["XX"]],
+                rotation_shapes: { Ars: [["XX"], ["XX"]] },
+            )],
+        )"#);
+        assert!(matches!(result, Err(PieceSetLoaderError::RotationCount(name)) if name == "O"));
+    }
+
+    /// アセットの標準ブロックセットが、組み込みのブロックセットと同じ内容か確認する
+    /// セルの順番（ブロックのID）は定義の仕方で変わるため、並べ替えて比べる
+    #[test]
+    fn standard_asset_matches_builtin() {
+        let asset = parse(include_str!("../../../assets/pieces/standard.pieces.ron")).unwrap();
+        let builtin = PieceSet::standard();
+        let sorted = |shape: &Shape| {
+            let mut cells = shape.clone();
+            cells.sort_by_key(|cell| (cell.y, cell.x));
+            cells
+        };
+
+        assert_eq!(asset.name, builtin.name);
+        assert_eq!(asset.first, builtin.first);
+        assert_eq!(asset.pieces.len(), builtin.pieces.len());
+        for (asset, builtin) in asset.pieces.iter().zip(&builtin.pieces) {
+            assert_eq!(asset.name, builtin.name);
+            assert_eq!(asset.color.to_srgba(), builtin.color.to_srgba(), "color of {}", asset.name);
+            for (asset_shape, builtin_shape) in asset.shapes.iter().zip(&builtin.shapes) {
+                assert_eq!(sorted(asset_shape), sorted(builtin_shape), "shape of {}", asset.name);
+            }
+            assert_eq!(asset.rotation_shapes.len(), builtin.rotation_shapes.len(), "rotation shapes of {}", asset.name);
+            for (kind, builtin_shapes) in &builtin.rotation_shapes {
+                let asset_shapes = &asset.rotation_shapes[kind];
+                for (asset_shape, builtin_shape) in asset_shapes.iter().zip(builtin_shapes) {
+                    assert_eq!(sorted(asset_shape), sorted(builtin_shape), "{:?} shape of {}", kind, asset.name);
+                }
+            }
+            assert_eq!(asset.kicks, builtin.kicks, "kicks of {}", asset.name);
+            assert_eq!(asset.ars_kick, builtin.ars_kick, "ARS kick of {}", asset.name);
+            assert_eq!(asset.spin, builtin.spin, "spin of {}", asset.name);
+            assert_eq!(asset.spawn_offset, builtin.spawn_offset, "spawn offset of {}", asset.name);
+            assert_eq!(asset.preview_offset, builtin.preview_offset, "preview offset of {}", asset.name);
+        }
+    }
+}
@@ -1,16 +1,47 @@
 pub use super::{
     PlayerBlock,
     Block,
+    BlockLifetime,
     BlockMap,
+    BlockAction,
     CurrentBlocks,
+    BlockRotationSystem,
+    BlockGenerator,
+    BlockPieces,
     HoldBlocks,
     NextBlocks,
+    ScoreChain,
+    GarbageQueue,
+    GarbageTimer,
     FallingTimer,
+    Gravity,
+    LockDelay,
+    PhaseTimer,
+    InitialActions,
+    ClearingRows,
+    Handling,
     MoveLeftTimer,
     MoveRightTimer,
     MoveBottomTimer,
 };
 pub use super::blockdata::*;
-pub use super::blockrandomizer::BlockRandomizer;
 pub use super::blocktype::BlockType;
+pub use super::board::Board;
 pub use super::fielddata::*;
+pub use super::garbage::GarbageStyle;
+pub use super::gravity::{
+    FRAMES_PER_SECOND,
+    GravityCurve,
+};
+pub use super::piecegenerator::GeneratorKind;
+pub use super::pieceset::PieceSet;
+pub use super::rotationsystem::{
+    RotationKind,
+    RotationSystem,
+};
+pub use super::ruleset::{
+    LevelProgress,
+    Ruleset,
+    ScoringKind,
+    StackVisibility,
+};
@@ -0,0 +1,181 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use super::prelude::*;
+use super::board::Board;
+use super::pieceset::{
+    ArsKick,
+    Shape,
+};
+
+/// 回転に成功した時の位置のずれと、何番目の壁蹴りで成功したかを表す構造体
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Kick {
+    pub offset: IVec2,
+    pub index: usize,
+}
+
+/// ブロックの形状と回転のルールを定義するトレイト
+/// モードごとに実装を切り替えることで、回転の挙動を変更できる
+pub trait RotationSystem: Send + Sync {
+    /// ブロックの4回転分の形状データを返すメソッド
+    /// ブロックセットに回転ルール独自の形状が定義されていれば、その形状に差し替える
+    fn blockdata(&self, pieces: &PieceSet, blocktype: BlockType) -> [Shape; MAX_BLOCK_COUNT];
+
+    /// 現在のブロックを指定された回転状態に回転させるメソッド
+    /// 回転できる場合は位置のずれを、できない場合は`None`を返す
+    ///
+    /// # Arguments
+    /// * board - 衝突判定に使用する盤面
+    /// * pieces - 壁蹴りデータや回転の設定を取得するブロックセット
+    /// * current - 回転前のブロック
+    /// * blockid - 回転後のブロックの回転状態
+    fn rotate(&self, board: &Board, pieces: &PieceSet, current: &CurrentBlocks, blockid: usize) -> Option<Kick>;
+}
+
+/// 回転ルールの種類
+/// デフォルト以外はモードから選択される
+/// ブロックセットのアセットでは、回転ルール独自の形状の名前に使用する
+#[allow(dead_code)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, Deserialize)]
+pub enum RotationKind {
+    /// スーパーローテーションシステム（ガイドライン準拠）
+    #[default]
+    Srs,
+    /// アリカ・ローテーションシステム（TGM準拠）
+    Ars,
+    /// NES版テトリスの回転（壁蹴りなし）
+    Nes,
+    /// 衝突したら上や横に押し出す独自の回転
+    Classic,
+}
+
+impl RotationKind {
+    /// 回転ルールの実装を生成するメソッド
+    pub fn system(&self) -> Box<dyn RotationSystem> {
+        match self {
+            RotationKind::Srs => Box::new(Srs),
+            RotationKind::Ars => Box::new(Ars),
+            RotationKind::Nes => Box::new(Nes),
+            RotationKind::Classic => Box::new(Classic),
+        }
+    }
+}
+
+/// 渡された壁蹴りデータを順番に試し、最初に衝突しなかったものを返す関数
+fn first_fit(board: &Board, current: &CurrentBlocks, blockid: usize, kicks: &[IVec2]) -> Option<Kick> {
+    kicks
+        .iter()
+        .enumerate()
+        .find(|(_, offset)| board.fits(&current.cells_at(blockid, current.pos + **offset)))
+        .map(|(index, offset)| Kick { offset: *offset, index })
+}
+
+/// スーパーローテーションシステム
+/// ブロックセットに定義された壁蹴りを順番に試す
+pub struct Srs;
+
+impl RotationSystem for Srs {
+    fn blockdata(&self, pieces: &PieceSet, blocktype: BlockType) -> [Shape; MAX_BLOCK_COUNT] {
+        pieces.shapes(blocktype).clone()
+    }
+
+    fn rotate(&self, board: &Board, pieces: &PieceSet, current: &CurrentBlocks, blockid: usize) -> Option<Kick> {
+        let kicks = pieces.piece(current.blocktype).kicks.kicks(current.blockid, blockid);
+        first_fit(board, current, blockid, kicks)
+    }
+}
+
+/// アリカ・ローテーションシステム
+/// 回転できなければ右、左の順に1マスずらして試す
+/// ずらすかどうかと中央列ルールは、ブロックセットの`ars_kick`に従う
+pub struct Ars;
+
+impl RotationSystem for Ars {
+    fn blockdata(&self, pieces: &PieceSet, blocktype: BlockType) -> [Shape; MAX_BLOCK_COUNT] {
+        pieces.rotation_shapes(blocktype, RotationKind::Ars).clone()
+    }
+
+    fn rotate(&self, board: &Board, pieces: &PieceSet, current: &CurrentBlocks, blockid: usize) -> Option<Kick> {
+        let kicks = [IVec2::ZERO, IVec2::new(1, 0), IVec2::new(-1, 0)];
+        let ars_kick = pieces.piece(current.blocktype).ars_kick;
+
+        if ars_kick == ArsKick::Never {
+            return first_fit(board, current, blockid, &kicks[..1]);
+        }
+
+        // 中央列ルール：回転後のブロックを上の行から順に調べ、
+        // 最初に衝突したセルが中央列であれば壁蹴りを行わない
+        let center_rule = ars_kick == ArsKick::CenterColumn;
+        let cells = current.cells_at(blockid, current.pos);
+        let blocked = cells
+            .iter()
+            .find(|cell| !board.is_free(cell.x, cell.y));
+        if let Some(cell) = blocked {
+            if center_rule && cell.x - current.pos.x == 1 {
+                return None;
+            }
+        }
+
+        first_fit(board, current, blockid, &kicks)
+    }
+}
+
+/// NES版テトリスの回転
+/// 壁蹴りを行わず、衝突した場合は回転しない
+pub struct Nes;
+
+impl RotationSystem for Nes {
+    fn blockdata(&self, pieces: &PieceSet, blocktype: BlockType) -> [Shape; MAX_BLOCK_COUNT] {
+        pieces.rotation_shapes(blocktype, RotationKind::Nes).clone()
+    }
+
+    fn rotate(&self, board: &Board, _pieces: &PieceSet, current: &CurrentBlocks, blockid: usize) -> Option<Kick> {
+        first_fit(board, current, blockid, &[IVec2::ZERO])
+    }
+}
+
+/// 衝突したら上や横に押し出す独自の回転
+/// 押し出しが規定回数以上になった場合は回転しない
+pub struct Classic;
+
+impl RotationSystem for Classic {
+    fn blockdata(&self, pieces: &PieceSet, blocktype: BlockType) -> [Shape; MAX_BLOCK_COUNT] {
+        pieces.rotation_shapes(blocktype, RotationKind::Classic).clone()
+    }
+
+    fn rotate(&self, board: &Board, _pieces: &PieceSet, current: &CurrentBlocks, blockid: usize) -> Option<Kick> {
+        // 衝突の回数をカウント
+        let mut count = 0;
+        // 衝突を避けるために動かした位置
+        let mut offset = IVec2::ZERO;
+        // 衝突をチェック
+        while count < MAX_COLLISION_COUNT {
+            // 回転時のブロックの位置を取得
+            let cells = current.cells_at(blockid, current.pos + offset);
+
+            // フィールド左側の衝突判定
+            if cells.iter().any(|cell| cell.x < 0) {
+                offset.x += 1;
+            }
+            // フィールド右側の衝突判定
+            else if cells.iter().any(|cell| cell.x >= board.width() as i32) {
+                offset.x -= 1;
+            }
+            // フィールド下側、ブロック同士の衝突判定
+            else if !board.fits(&cells) {
+                offset.y -= 1;
+            }
+            // 衝突がなければループを抜ける
+            else { break; }
+
+            count += 1;
+        }
+
+        // もし衝突判定が規定回数以上あった場合、回転を行わない
+        if count >= MAX_COLLISION_COUNT {
+            return None;
+        }
+        Some(Kick { offset, index: count })
+    }
+}
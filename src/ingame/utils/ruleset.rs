@@ -0,0 +1,348 @@
+use bevy::prelude::*;
+
+use crate::PATH_PIECES_STANDARD;
+use super::Handling;
+use super::blockdata::*;
+use super::fielddata::FieldLayout;
+use super::garbage::GarbageStyle;
+use super::gravity::{
+    GravityCurve,
+    FRAMES_PER_SECOND,
+};
+
+/// レベルの上がり方
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum LevelProgress {
+    /// 消去したライン数の合計から計算する（`lines_per_level`ごとに1上がる）
+    #[default]
+    Lines,
+    /// ブロックが出現するたびに1、ラインを消去したらライン数だけ上がる（TGM準拠）
+    /// セクションの区切りの手前（x99と998）では、ライン消去でしか上がらない
+    Tgm,
+    /// 開始レベルに応じたライン数で最初に上がり、その後は`lines_per_level`ごとに1上がる（NES版テトリス準拠）
+    Nes,
+}
+
+/// 固定したブロックの見え方
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum StackVisibility {
+    /// ずっと見えている
+    #[default]
+    Visible,
+    /// 値（秒）が経つと、だんだん見えなくなる
+    Fading(f32),
+    /// 固定した瞬間に見えなくなる
+    Invisible,
+}
+
+/// ライン消去の点数の計算方法
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ScoringKind {
+    /// Tスピン、B2B、コンボ、パーフェクトクリアのボーナスがある（ガイドライン準拠）
+    #[default]
+    Guideline,
+    /// 消去したライン数の点数に（レベル + 1）を掛ける（NES版テトリス準拠）
+    Nes,
+    /// 点数を計算しない（段位で競うモード）
+    None,
+}
+
+/// ゲームのルールを管理するリソース
+/// モードごとに値を差し替えることで、ゲームの挙動を変更できる
+/// - lock_delay: ブロックが接地してから固定されるまでの秒数
+/// - lock_reset_limit: 接地中に固定までの時間をリセットできる回数（0ならリセットしない）
+/// - lock_on_gravity: 接地してから次に落下するタイミングで固定するか（`lock_delay`の代わりに落下間隔を使う）
+/// - gravity: レベルから落下速度を決める重力曲線
+/// - start_level: ゲーム開始時のレベル
+/// - lines_per_level: レベルが上がるまでに消去するライン数
+/// - level_progress: レベルの上がり方
+/// - are: ブロックが固定されてから次のブロックが出現するまでの秒数
+/// - are_by_height: 固定した高さで出現待ちを長くするか（NES版テトリス準拠、下の2行に固定した時が`are`）
+/// - line_clear_delay: ラインが揃ってから消去されるまでの秒数
+/// - width: フィールドの横幅（列数）
+/// - visible_height: 見えているフィールドの高さ（行数）
+/// - buffer_height: 見えているフィールドより上にブロックを置ける行数（バッファゾーン）
+/// - block_scale: 盤面の1マスを画面上で何マス四方に表示するか（ビッグモードでは2）
+/// - hold: ホールドを使えるか
+/// - hard_drop: ハードドロップを使えるか
+/// - previews: 表示する次のブロックの数（`NEXT_BLOCK_COUNT - 1`まで）
+/// - ghost: ブロックの落下地点を表示するか
+/// - scoring: ライン消去の点数の計算方法
+/// - stack: 固定したブロックの見え方
+/// - handling: ルールで決まっている操作感（`None`ならプレイヤーの設定を使う）
+/// - irs: 出現待ちの間に押していた回転キーを、出現するブロックに適用するか
+/// - ihs: 出現待ちの間に押していたホールドキーを、出現するブロックに適用するか
+/// - piece_set: ブロックの種類を定義したアセットのパス（`None`なら組み込みの7種類）
+/// - garbage: せり上がるおじゃまブロックの穴の空き方
+/// - start_garbage: ゲーム開始時に盤面の下に並べるおじゃまブロックの行数
+/// - garbage_interval: おじゃまブロックが時間でせり上がる最初の間隔（秒、`None`なら時間ではせり上がらない）
+/// - perfect_clear_attack: 対戦ルールでパーフェクトクリアした時の攻撃のライン数（0なら攻撃しない）
+#[derive(Resource, Clone, Debug)]
+pub struct Ruleset {
+    pub lock_delay: f32,
+    pub lock_reset_limit: usize,
+    pub lock_on_gravity: bool,
+    pub gravity: GravityCurve,
+    pub start_level: usize,
+    pub lines_per_level: usize,
+    pub level_progress: LevelProgress,
+    pub are: f32,
+    pub are_by_height: bool,
+    pub line_clear_delay: f32,
+    pub width: usize,
+    pub visible_height: usize,
+    pub buffer_height: usize,
+    pub block_scale: usize,
+    pub hold: bool,
+    pub hard_drop: bool,
+    pub previews: usize,
+    pub ghost: bool,
+    pub scoring: ScoringKind,
+    pub stack: StackVisibility,
+    pub handling: Option<Handling>,
+    pub irs: bool,
+    pub ihs: bool,
+    pub piece_set: Option<&'static str>,
+    pub garbage: GarbageStyle,
+    pub start_garbage: usize,
+    pub garbage_interval: Option<f32>,
+    pub perfect_clear_attack: usize,
+}
+
+impl Ruleset {
+    /// 消去したライン数の合計からレベルを計算するメソッド
+    pub fn level(&self, lines: usize) -> usize {
+        let per_level = self.lines_per_level.max(1);
+        match self.level_progress {
+            LevelProgress::Nes => {
+                // 最初のレベルアップまでのライン数は、開始レベル x 10 + 10 と
+                // 開始レベル x 10 - 50（100以上）の小さい方になる
+                let start_lines = self.start_level * per_level;
+                let first = (start_lines + per_level).min(
+                    start_lines.saturating_sub(NES_FIRST_LEVEL_UP_OFFSET).max(NES_FIRST_LEVEL_UP_MIN),
+                );
+                if lines < first {
+                    self.start_level
+                } else {
+                    self.start_level + 1 + (lines - first) / per_level
+                }
+            }
+            LevelProgress::Lines | LevelProgress::Tgm => self.start_level + lines / per_level,
+        }
+    }
+
+    /// ブロックを固定した後のレベルを計算するメソッド
+    ///
+    /// # Arguments
+    /// * level - 固定する前のレベル
+    /// * lines - 消去したライン数の合計（固定したブロックで消去した分を含む）
+    /// * count - 固定したブロックで消去したライン数
+    pub fn next_level(&self, level: usize, lines: usize, count: usize) -> usize {
+        match self.level_progress {
+            LevelProgress::Lines | LevelProgress::Nes => self.level(lines),
+            LevelProgress::Tgm => (level + count).min(TGM_MAX_LEVEL),
+        }
+    }
+
+    /// 次のブロックが出現した時のレベルを計算するメソッド
+    /// TGMのルールでは1上がるが、セクションの区切りの手前（x99と998）では上がらない
+    pub fn spawn_level(&self, level: usize) -> usize {
+        match self.level_progress {
+            LevelProgress::Lines | LevelProgress::Nes => level,
+            LevelProgress::Tgm => {
+                let stopped = level % TGM_SECTION_LEVELS == TGM_SECTION_LEVELS - 1
+                    || level >= TGM_MAX_LEVEL - 1;
+                if stopped { level } else { level + 1 }
+            }
+        }
+    }
+
+    /// ブロックが接地してから固定されるまでの秒数を返すメソッド
+    /// 次に落下するタイミングで固定するルールでは、レベルの落下間隔になる
+    pub fn lock_time(&self, level: usize) -> f32 {
+        if self.lock_on_gravity {
+            self.gravity.interval(level)
+        } else {
+            self.lock_delay
+        }
+    }
+
+    /// ブロックが固定されてから次のブロックが出現するまでの秒数を返すメソッド
+    /// 固定した高さで変えるルールでは、4行上がるごとに2フレーム長くなる（最大で8フレーム）
+    ///
+    /// # Arguments
+    /// * height - 固定したブロックの一番下のセルの、盤面の下から数えた行（一番下が0）
+    pub fn are_time(&self, height: usize) -> f32 {
+        if !self.are_by_height {
+            return self.are;
+        }
+        let steps = ((height + NES_ARE_HEIGHT_OFFSET) / NES_ARE_ROWS_PER_STEP).min(NES_ARE_MAX_STEPS);
+        self.are + steps as f32 * NES_ARE_STEP_FRAMES / FRAMES_PER_SECOND
+    }
+
+    /// 実際に使う操作感を返すメソッド
+    /// ルールで操作感が決まっていれば、プレイヤーの設定より優先する
+    pub fn effective_handling<'a>(&'a self, player: &'a Handling) -> &'a Handling {
+        self.handling.as_ref().unwrap_or(player)
+    }
+
+    /// フィールドの大きさと配置を返すメソッド
+    pub fn layout(&self) -> FieldLayout {
+        FieldLayout::new(self.width, self.visible_height, self.buffer_height, self.block_scale)
+    }
+}
+
+impl Default for Ruleset {
+    fn default() -> Self {
+        Self {
+            lock_delay: BLOCK_LOCK_DELAY,
+            lock_reset_limit: MAX_LOCK_RESET_COUNT,
+            lock_on_gravity: false,
+            gravity: GravityCurve::Guideline,
+            start_level: START_LEVEL,
+            lines_per_level: LINES_PER_LEVEL,
+            level_progress: LevelProgress::default(),
+            are: BLOCK_ARE,
+            are_by_height: false,
+            line_clear_delay: LINE_CLEAR_DELAY,
+            width: BOARD_WIDTH,
+            visible_height: BOARD_VISIBLE_HEIGHT,
+            buffer_height: BUFFER_HEIGHT,
+            block_scale: 1,
+            hold: true,
+            hard_drop: true,
+            previews: NEXT_BLOCK_COUNT - 1,
+            ghost: true,
+            scoring: ScoringKind::default(),
+            stack: StackVisibility::default(),
+            handling: None,
+            irs: true,
+            ihs: true,
+            piece_set: Some(PATH_PIECES_STANDARD),
+            garbage: GarbageStyle::default(),
+            start_garbage: 0,
+            garbage_interval: None,
+            perfect_clear_attack: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 指定したレベルの上がり方のルールを返す関数
+    fn ruleset(level_progress: LevelProgress, start_level: usize) -> Ruleset {
+        Ruleset {
+            level_progress,
+            start_level,
+            lines_per_level: 10,
+            ..default()
+        }
+    }
+
+    #[test]
+    fn nes_first_level_up() {
+        let table = [
+            // (開始レベル, 最初にレベルが上がるライン数)
+            (0, 10),
+            (9, 100),
+            (15, 100),
+            (19, 140),
+        ];
+        for (start, first) in table {
+            let ruleset = ruleset(LevelProgress::Nes, start);
+            assert_eq!(ruleset.level(0), start, "start {}", start);
+            assert_eq!(ruleset.level(first - 1), start, "start {}", start);
+            assert_eq!(ruleset.level(first), start + 1, "start {}", start);
+            // 2回目以降は10ラインごと
+            assert_eq!(ruleset.level(first + 9), start + 1, "start {}", start);
+            assert_eq!(ruleset.level(first + 10), start + 2, "start {}", start);
+        }
+    }
+
+    #[test]
+    fn lines_level_up_every_ten_lines() {
+        let ruleset = ruleset(LevelProgress::Lines, 1);
+        assert_eq!(ruleset.level(9), 1);
+        assert_eq!(ruleset.level(10), 2);
+        assert_eq!(ruleset.next_level(1, 25, 4), 3);
+    }
+
+    #[test]
+    fn tgm_level_stops_at_section_end() {
+        let ruleset = ruleset(LevelProgress::Tgm, 0);
+        let table = [
+            // (出現する前のレベル, 出現した後のレベル)
+            (0, 1),
+            (98, 99),
+            (99, 99),
+            (100, 101),
+            (997, 998),
+            (998, 998),
+            (999, 999),
+        ];
+        for (level, expected) in table {
+            assert_eq!(ruleset.spawn_level(level), expected, "level {}", level);
+        }
+    }
+
+    #[test]
+    fn tgm_line_clears_pass_section_end() {
+        let ruleset = ruleset(LevelProgress::Tgm, 0);
+        let table = [
+            // (固定する前のレベル, 消去したライン数, 固定した後のレベル)
+            (98, 0, 98),
+            (98, 1, 99),
+            (99, 0, 99),
+            (99, 1, 100),
+            (97, 4, 101),
+            (998, 1, 999),
+            (997, 4, 999),
+        ];
+        for (level, count, expected) in table {
+            assert_eq!(ruleset.next_level(level, 0, count), expected, "level {}, {} lines", level, count);
+        }
+
+        // 98で出現したブロックで消去しなければ99で止まり、1ライン消去すると100を越えて進む
+        let level = ruleset.spawn_level(98);
+        let level = ruleset.next_level(level, 0, 0);
+        assert_eq!(ruleset.spawn_level(level), 99);
+        let level = ruleset.next_level(level, 0, 1);
+        assert_eq!(ruleset.spawn_level(level), 101);
+    }
+
+    #[test]
+    fn spawn_does_not_change_line_based_levels() {
+        for progress in [LevelProgress::Lines, LevelProgress::Nes] {
+            assert_eq!(ruleset(progress, 0).spawn_level(99), 99);
+        }
+    }
+
+    #[test]
+    fn nes_are_by_lock_height() {
+        let ruleset = Ruleset {
+            are: 10.0 / FRAMES_PER_SECOND,
+            are_by_height: true,
+            ..default()
+        };
+        let table = [
+            // (固定した高さ, 出現待ちのフレーム数)
+            (0, 10.0),
+            (1, 10.0),
+            (2, 12.0),
+            (5, 12.0),
+            (6, 14.0),
+            (10, 16.0),
+            (14, 18.0),
+            (19, 18.0),
+        ];
+        for (height, frames) in table {
+            let are = ruleset.are_time(height) * FRAMES_PER_SECOND;
+            assert!((are - frames).abs() < 1e-3, "height {}: {}", height, are);
+        }
+        // 高さで変えないルールでは常に同じ
+        let fixed = Ruleset::default();
+        assert_eq!(fixed.are_time(0), fixed.are_time(19));
+    }
+}
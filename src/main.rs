@@ -1,3 +1,4 @@
+
 use bevy::{
     prelude::*,
     log::LogPlugin,
@@ -5,6 +6,7 @@ use bevy::{
     window::WindowResolution,
 };
 
+mod gamemode;
 mod mainmenu;
 mod ingame;
 mod gameover;
@@ -20,6 +22,9 @@ const PATH_IMAGE_HOUSE: &str = "images/house-dark.png";
 const PATH_IMAGE_RETRY: &str = "images/rotate-left-dark.png";
 const PATH_SOUND_BGM: &str = "ittoku-tetris/bgm.ogg";
 const PATH_SOUND_CLICK: &str = "sounds/click.ogg";
+const PATH_PIECES_STANDARD: &str = "pieces/standard.pieces.ron";
+
+const ARG_SEED: &str = "--seed";
 
 const GRID_SIZE: f32 = 20.0;
 const GRID_SIZE_HALF: f32 = GRID_SIZE / 2.0;
@@ -36,6 +41,101 @@ enum AppState {
 #[derive(Resource, Debug, Deref, DerefMut)]
 pub struct Score(pub usize);
 
+/// 消去したライン数の合計を管理するリソース
+#[derive(Resource, Debug, Deref, DerefMut)]
+pub struct ClearedLines(pub usize);
+
+/// 置いたブロックの数を管理するリソース
+#[derive(Resource, Debug, Deref, DerefMut)]
+pub struct PlacedPieces(pub usize);
+
+/// 現在のレベルを管理するリソース
+#[derive(Resource, Debug, Deref, DerefMut)]
+pub struct Level(pub usize);
+
+/// ゲームオーバーになった原因を管理するリソース
+/// - BlockOut: 出現したブロックが既にあるブロックと重なった
+/// - LockOut: ブロックが全て見えているフィールドより上で固定された
+/// - TopOut: ブロックがバッファゾーンより上まで積み上がった
+/// - Cleared: ゲームモードの目標を達成した
+/// - TimeUp: ゲームモードの制限時間が終わった
+#[derive(Resource, Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum GameoverReason {
+    #[default]
+    BlockOut,
+    LockOut,
+    TopOut,
+    Cleared,
+    TimeUp,
+}
+
+impl GameoverReason {
+    /// ゲームオーバー画面に表示するメッセージを返すメソッド
+    pub fn message(&self) -> &'static str {
+        match self {
+            GameoverReason::BlockOut => "ブロックアウト",
+            GameoverReason::LockOut => "ロックアウト",
+            GameoverReason::TopOut => "トップアウト",
+            GameoverReason::Cleared => "もくひょうたっせい",
+            GameoverReason::TimeUp => "タイムアップ",
+        }
+    }
+
+    /// ゲームモードをクリアしてゲームが終わったか判定するメソッド
+    /// 制限時間まで遊びきった場合もクリアとして扱う
+    pub fn is_clear(&self) -> bool {
+        matches!(self, GameoverReason::Cleared | GameoverReason::TimeUp)
+    }
+}
+
+/// ブロックの出現順を決める乱数のシード値を管理するリソース
+/// - value: 現在のゲームで使用しているシード値
+/// - fixed: コマンドライン引数やメインメニューで指定されたシード値（指定がなければゲームごとにランダム）
+#[derive(Resource, Debug, Default)]
+pub struct Seed {
+    pub value: u64,
+    pub fixed: Option<u64>,
+}
+
+impl Seed {
+    pub fn new(fixed: Option<u64>) -> Self {
+        Self {
+            value: fixed.unwrap_or_default(),
+            fixed,
+        }
+    }
+
+    /// ゲーム開始時にシード値を決めるメソッド
+    /// 指定されたシード値があればそれを、なければランダムな値を使用する
+    /// ランダムな値は、メインメニューで表示、入力しやすいよう32ビットの範囲にする
+    pub fn roll(&mut self) -> u64 {
+        self.value = self.fixed.unwrap_or_else(|| rand::random::<u32>().into());
+        self.value
+    }
+}
+
+/// コマンドライン引数からシード値を取得する関数
+/// `--seed 1234`または`--seed=1234`の形式で指定する
+/// 数値として読めない値は警告をログに出力して無視するため、`LogPlugin`を追加した後に呼ぶ
+fn seed_from_args() -> Option<u64> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let value = args.iter().enumerate().find_map(|(index, arg)| {
+        if arg == ARG_SEED {
+            args.get(index + 1).cloned()
+        } else {
+            arg.strip_prefix(ARG_SEED)?.strip_prefix('=').map(str::to_string)
+        }
+    })?;
+
+    match value.parse() {
+        Ok(seed) => Some(seed),
+        Err(error) => {
+            warn!("invalid seed {:?}: {}", value, error);
+            None
+        }
+    }
+}
+
 fn main() {
     let window_size = WINDOW_SIZE.as_uvec2();
 
@@ -64,6 +164,16 @@ fn main() {
         .insert_resource(ClearColor(BACKGROUND_COLOR))
         .insert_resource(Time::<Fixed>::from_seconds(1.0 / 60.0))
         .insert_resource(Score(0))
+        .insert_resource(ClearedLines(0))
+        .insert_resource(PlacedPieces(0))
+        .insert_resource(Level(0))
+        .insert_resource(GameoverReason::default())
+        .insert_resource(Seed::new(seed_from_args()))
+        .insert_resource(gamemode::GameMode::default())
+        .insert_resource(gamemode::ModeOptions::default())
+        .insert_resource(gamemode::PlayTime::default())
+        .insert_resource(gamemode::Grade::default())
+        .insert_resource(gamemode::SectionTimes::default())
         .add_plugins(mainmenu::MainmenuPlugin)
         .add_plugins(ingame::IngamePlugin)
         .add_plugins(gameover::GameoverPlugin)
@@ -76,11 +186,17 @@ fn main() {
 fn setup(mut commands: Commands) {
     info_once!("setup");
 
-    commands.spawn(Camera2d::default());
+    commands.spawn(Camera2d);
 }
 
-fn reset_score(mut score: ResMut<Score>) {
+fn reset_score(
+    mut score: ResMut<Score>,
+    mut lines: ResMut<ClearedLines>,
+    mut pieces: ResMut<PlacedPieces>,
+) {
     info_once!("reset_score");
 
     **score = 0;
+    **lines = 0;
+    **pieces = 0;
 }
@@ -5,12 +5,18 @@ use crate::{
     GAMETITLE,
     PATH_FONT,
     AppState,
+    Seed,
+};
+use crate::gamemode::{
+    GameMode,
+    ModeOption,
+    ModeOptions,
 };
 
 const ROOT_WIDTH: Val = Val::Percent(100.0);
 const ROOT_HEIGHT: Val = Val::Percent(100.0);
 
-const BOARD_SIZE: Vec2 = Vec2::new(360.0, 270.0);
+const BOARD_SIZE: Vec2 = Vec2::new(360.0, 310.0);
 const BOARD_WIDTH: Val = Val::Px(BOARD_SIZE.x);
 const BOARD_HEIGHT: Val = Val::Px(BOARD_SIZE.y);
 const BOARD_LEFT: Val = Val::Px(WINDOW_SIZE.x / 2.0 - BOARD_SIZE.x / 2.0);
@@ -30,6 +36,21 @@ const PLAY_FONT_SIZE: f32 = 20.0;
 const PLAY_COLOR: Color = Color::srgb(0.79, 0.83, 0.96);
 const PLAY_COLOR_HOVER: Color = Color::srgb(0.31, 0.84, 0.75);
 
+const MODE_WIDTH: Val = Val::Px(BOARD_SIZE.x - 64.0);
+const MODE_HEIGHT: Val = Val::Px(40.0);
+const MODE_FONT_SIZE: f32 = 20.0;
+const MODE_COLOR: Color = Color::srgb(0.79, 0.83, 0.96);
+const MODE_BUTTON_SIZE: Val = Val::Px(40.0);
+const MODE_BUTTON_COLOR: Color = BOARD_COLOR;
+const MODE_BUTTON_COLOR_HOVER: Color = Color::srgb(0.39, 0.43, 0.65);
+const MODE_PREV_TEXT: &str = "<";
+const MODE_NEXT_TEXT: &str = ">";
+
+const DESCRIPTION_FONT_SIZE: f32 = 16.0;
+
+const SEED_TEXT: &str = "シード";
+const SEED_RANDOM_TEXT: &str = "ランダム";
+
 const BORDER_SIZE: Val = Val::Px(4.0);
 const BORDER_COLOR: Color = Color::srgb(0.79, 0.83, 0.96);
 const BORDER_RADIUS: Val = Val::Px(10.0);
@@ -40,6 +61,42 @@ struct Mainmenu;
 #[derive(Component)]
 struct Play;
 
+/// ゲームモードを切り替えるボタンのコンポーネント
+/// 値は押された時にゲームモードを進める数
+#[derive(Component)]
+struct ModeButton(i32);
+
+/// 選択中のゲームモードの名前を表示するテキストのコンポーネント
+#[derive(Component)]
+struct ModeName;
+
+/// 選択中のゲームモードの説明を表示するテキストのコンポーネント
+#[derive(Component)]
+struct ModeDescription;
+
+/// ゲームモードの設定を切り替えるボタンのコンポーネント
+/// 値は切り替える設定と、押された時に進める数
+#[derive(Component)]
+struct OptionButton(ModeOption, i32);
+
+/// ゲームモードの設定の行のコンポーネント
+/// 選択中のゲームモードで使わない設定の行は隠す
+#[derive(Component)]
+struct OptionRow(ModeOption);
+
+/// ゲームモードの設定の値を表示するテキストのコンポーネント
+#[derive(Component)]
+struct OptionText(ModeOption);
+
+/// シード値を切り替えるボタンのコンポーネント
+/// どちらのボタンでも、ランダムと前回遊んだシード値を切り替える
+#[derive(Component)]
+struct SeedButton;
+
+/// シード値を表示するテキストのコンポーネント
+#[derive(Component)]
+struct SeedText;
+
 impl Mainmenu {
     /// メインメニュー画面のルートノードを生成します
     ///
@@ -157,6 +214,169 @@ impl Mainmenu {
             TextColor(PLAY_COLOR),
         )
     }
+
+    /// ゲームモードを選択するボタンとテキストの配置を決めるノード
+    ///
+    /// Returns:
+    /// * `Self`: Mainmenuのインスタンス。
+    /// * `Node`: モード選択の配置を決めるノード
+    fn from_mode_list() -> (Self, Node) {
+        (
+            Self,
+            Node {
+                width: MODE_WIDTH,
+                height: MODE_HEIGHT,
+                flex_direction: FlexDirection::Row,
+                justify_content: JustifyContent::SpaceBetween,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            }
+        )
+    }
+
+    /// ゲームモードを切り替えるボタンを生成します。
+    ///
+    /// Returns:
+    /// * `Self`: Mainmenuのインスタンス。
+    /// * `Node`: モード切り替えボタンを表すノード。
+    /// * `BackgroundColor`: 背景色
+    /// * `BorderColor`: ボーダーの色
+    /// * `BorderRadius`: ボーダーのラディウス
+    /// * `Button`: ボタンコンポーネント
+    fn from_mode_button() -> (Self, Node, BackgroundColor, BorderColor, BorderRadius, Button) {
+        (
+            Self,
+            Node {
+                width: MODE_BUTTON_SIZE,
+                height: MODE_BUTTON_SIZE,
+                border: UiRect::all(BORDER_SIZE),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            BackgroundColor(MODE_BUTTON_COLOR),
+            BorderColor::all(BORDER_COLOR),
+            BorderRadius::all(BORDER_RADIUS),
+            Button,
+        )
+    }
+
+    /// ゲームモードの名前や、切り替えボタンの矢印のテキストを生成します。
+    ///
+    /// Params:
+    /// * `font`: テキストに使用するフォント
+    /// * `text`: 表示するテキスト
+    ///
+    /// Returns:
+    /// * `Self`: Mainmenuのインスタンス。
+    /// * `Text`: ゲームモードのテキスト。
+    /// * `TextFont`: フォントスタイル。
+    /// * `TextColor`: テキストの色
+    fn from_mode_text(font: Handle<Font>, text: &str) -> (Self, Text, TextFont, TextColor) {
+        (
+            Self,
+            Text::new(text),
+            TextFont {
+                font: font.clone(),
+                font_size: MODE_FONT_SIZE,
+                ..Default::default()
+            },
+            TextColor(MODE_COLOR),
+        )
+    }
+
+    /// ゲームモードの説明のテキストを生成します。
+    ///
+    /// Params:
+    /// * `font`: テキストに使用するフォント
+    /// * `mode`: 説明を表示するゲームモード
+    ///
+    /// Returns:
+    /// * `Self`: Mainmenuのインスタンス。
+    /// * `Text`: ゲームモードの説明のテキスト。
+    /// * `TextFont`: フォントスタイル。
+    /// * `TextColor`: テキストの色
+    fn from_description(font: Handle<Font>, mode: GameMode) -> (Self, Text, TextFont, TextColor) {
+        (
+            Self,
+            Text::new(mode.description()),
+            TextFont {
+                font: font.clone(),
+                font_size: DESCRIPTION_FONT_SIZE,
+                ..Default::default()
+            },
+            TextColor(MODE_COLOR),
+        )
+    }
+}
+
+/// 設定の名前と値を表示するテキストを返す関数
+fn option_text(option: ModeOption, options: &ModeOptions) -> String {
+    format!("{}  {}", option.name(), option.value_name(options.get(option)))
+}
+
+/// ゲームモードで設定を使うかどうかで、設定の行の表示を切り替える値を返す関数
+/// 使わない設定の行は場所も空けないようにする
+fn option_display(option: ModeOption, mode: GameMode) -> Display {
+    if mode.options().contains(&option) {
+        Display::Flex
+    } else {
+        Display::None
+    }
+}
+
+/// ゲームモードの設定を切り替えるボタンとテキストの行を生成する関数
+///
+/// # Arguments
+/// * font - テキストに使用するフォント
+/// * option - 切り替える設定
+/// * options - 現在の設定の値
+/// * mode - 選択中のゲームモード
+fn option_list(font: Handle<Font>, option: ModeOption, options: &ModeOptions, mode: GameMode) -> impl Bundle {
+    let (mainmenu, node) = Mainmenu::from_mode_list();
+    (
+        mainmenu,
+        Node { display: option_display(option, mode), ..node },
+        OptionRow(option),
+        children![
+            (Mainmenu::from_mode_button(), OptionButton(option, -1), children![(
+                Mainmenu::from_mode_text(font.clone(), MODE_PREV_TEXT),
+            )]),
+            (Mainmenu::from_mode_text(font.clone(), &option_text(option, options)), OptionText(option)),
+            (Mainmenu::from_mode_button(), OptionButton(option, 1), children![(
+                Mainmenu::from_mode_text(font.clone(), MODE_NEXT_TEXT),
+            )]),
+        ],
+    )
+}
+
+/// シード値を表示するテキストを返す関数
+/// シード値が指定されていなければ、ゲームごとにランダムになることを表示する
+fn seed_text(seed: &Seed) -> String {
+    match seed.fixed {
+        Some(value) => format!("{}  {}", SEED_TEXT, value),
+        None => format!("{}  {}", SEED_TEXT, SEED_RANDOM_TEXT),
+    }
+}
+
+/// シード値を切り替えるボタンとテキストの行を生成する関数
+///
+/// # Arguments
+/// * font - テキストに使用するフォント
+/// * seed - 現在のシード値
+fn seed_list(font: Handle<Font>, seed: &Seed) -> impl Bundle {
+    (
+        Mainmenu::from_mode_list(),
+        children![
+            (Mainmenu::from_mode_button(), SeedButton, children![(
+                Mainmenu::from_mode_text(font.clone(), MODE_PREV_TEXT),
+            )]),
+            (Mainmenu::from_mode_text(font.clone(), &seed_text(seed)), SeedText),
+            (Mainmenu::from_mode_button(), SeedButton, children![(
+                Mainmenu::from_mode_text(font.clone(), MODE_NEXT_TEXT),
+            )]),
+        ],
+    )
 }
 
 /// メインメニュー画面のセットアップを行う関数
@@ -164,11 +384,29 @@ impl Mainmenu {
 /// * root
 ///   * board
 ///     * mainmenu text
+///     * mode list
+///       * prev button
+///         * button text
+///       * mode name
+///       * next button
+///         * button text
+///     * mode description
+///     * option lists (level, height, randomizer)
+///       * prev button
+///       * option text
+///       * next button
+///     * seed list
+///       * prev button
+///       * seed text
+///       * next button
 ///     * play button
 ///       * button text
 fn setup(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
+    mode: Res<GameMode>,
+    options: Res<ModeOptions>,
+    seed: Res<Seed>,
 ) {
     info_once!("setup");
 
@@ -179,6 +417,20 @@ fn setup(
             Mainmenu::from_board(),
             children![
                 Mainmenu::from_title(font.clone()),
+                (Mainmenu::from_mode_list(), children![
+                    (Mainmenu::from_mode_button(), ModeButton(-1), children![(
+                        Mainmenu::from_mode_text(font.clone(), MODE_PREV_TEXT),
+                    )]),
+                    (Mainmenu::from_mode_text(font.clone(), mode.name()), ModeName),
+                    (Mainmenu::from_mode_button(), ModeButton(1), children![(
+                        Mainmenu::from_mode_text(font.clone(), MODE_NEXT_TEXT),
+                    )]),
+                ]),
+                (Mainmenu::from_description(font.clone(), *mode), ModeDescription),
+                option_list(font.clone(), ModeOption::Level, &options, *mode),
+                option_list(font.clone(), ModeOption::Height, &options, *mode),
+                option_list(font.clone(), ModeOption::Randomizer, &options, *mode),
+                seed_list(font.clone(), &seed),
                 (Mainmenu::from_button(), Play, children![(
                     Mainmenu::from_text(font.clone()), Play,
                 )],
@@ -218,6 +470,186 @@ fn play_button_system(
     Ok(())
 }
 
+/// モード切り替えボタンの挙動を決める関数
+/// ボタンが押されたら遊ぶゲームモードを切り替えます
+#[allow(clippy::type_complexity)]
+fn mode_button_system(
+    mut interaction_query: Query<
+    (&Interaction, &ModeButton, &mut BackgroundColor),
+    (Changed<Interaction>, With<Button>),
+    >,
+    mut mode: ResMut<GameMode>,
+) {
+    info_once!("mode_button_system");
+
+    // 全てのインタラクション状態を持つモード切り替えボタンに対して処理を行う
+    for (interaction, button, mut color) in &mut interaction_query {
+        match *interaction {
+            // ボタンが押された時の処理
+            Interaction::Pressed => {
+                *mode = mode.cycle(button.0);
+            }
+            // ボタンがホバーされた時の処理
+            Interaction::Hovered => {
+                *color = MODE_BUTTON_COLOR_HOVER.into();
+            }
+            // ボタンに何もされていない時の処理
+            Interaction::None => {
+                *color = MODE_BUTTON_COLOR.into();
+            }
+        }
+    }
+}
+
+/// 選択中のゲームモードの名前と説明を更新する関数
+fn update_mode_text(
+    mut name_query: Query<&mut Text, (With<ModeName>, Without<ModeDescription>)>,
+    mut description_query: Query<&mut Text, (With<ModeDescription>, Without<ModeName>)>,
+    mode: Res<GameMode>,
+) {
+    info_once!("update_mode_text");
+
+    for mut text in &mut name_query {
+        **text = mode.name().to_string();
+    }
+    for mut text in &mut description_query {
+        **text = mode.description().to_string();
+    }
+}
+
+/// 設定切り替えボタンの挙動を決める関数
+/// ボタンが押されたらゲームモードの設定を切り替えます
+#[allow(clippy::type_complexity)]
+fn option_button_system(
+    mut interaction_query: Query<
+    (&Interaction, &OptionButton, &mut BackgroundColor),
+    (Changed<Interaction>, With<Button>),
+    >,
+    mut options: ResMut<ModeOptions>,
+) {
+    info_once!("option_button_system");
+
+    // 全てのインタラクション状態を持つ設定切り替えボタンに対して処理を行う
+    for (interaction, button, mut color) in &mut interaction_query {
+        match *interaction {
+            // ボタンが押された時の処理
+            Interaction::Pressed => {
+                options.cycle(button.0, button.1);
+            }
+            // ボタンがホバーされた時の処理
+            Interaction::Hovered => {
+                *color = MODE_BUTTON_COLOR_HOVER.into();
+            }
+            // ボタンに何もされていない時の処理
+            Interaction::None => {
+                *color = MODE_BUTTON_COLOR.into();
+            }
+        }
+    }
+}
+
+/// ゲームモードの設定の値と、設定の行の表示を更新する関数
+fn update_option_text(
+    mut text_query: Query<(&mut Text, &OptionText)>,
+    mut row_query: Query<(&mut Node, &OptionRow)>,
+    options: Res<ModeOptions>,
+    mode: Res<GameMode>,
+) {
+    info_once!("update_option_text");
+
+    for (mut text, option) in &mut text_query {
+        **text = option_text(option.0, &options);
+    }
+    for (mut node, row) in &mut row_query {
+        node.display = option_display(row.0, *mode);
+    }
+}
+
+/// シード値切り替えボタンの挙動を決める関数
+/// ボタンが押されたらランダムと前回遊んだシード値を切り替えます
+#[allow(clippy::type_complexity)]
+fn seed_button_system(
+    mut interaction_query: Query<
+    (&Interaction, &mut BackgroundColor),
+    (Changed<Interaction>, With<SeedButton>),
+    >,
+    mut seed: ResMut<Seed>,
+) {
+    info_once!("seed_button_system");
+
+    // 全てのインタラクション状態を持つシード値切り替えボタンに対して処理を行う
+    for (interaction, mut color) in &mut interaction_query {
+        match *interaction {
+            // ボタンが押された時の処理
+            Interaction::Pressed => {
+                seed.fixed = match seed.fixed {
+                    Some(_) => None,
+                    None => Some(seed.value),
+                };
+            }
+            // ボタンがホバーされた時の処理
+            Interaction::Hovered => {
+                *color = MODE_BUTTON_COLOR_HOVER.into();
+            }
+            // ボタンに何もされていない時の処理
+            Interaction::None => {
+                *color = MODE_BUTTON_COLOR.into();
+            }
+        }
+    }
+}
+
+/// 数字キーの値を返す関数
+fn key_digit(key: KeyCode) -> Option<u64> {
+    match key {
+        KeyCode::Digit0 | KeyCode::Numpad0 => Some(0),
+        KeyCode::Digit1 | KeyCode::Numpad1 => Some(1),
+        KeyCode::Digit2 | KeyCode::Numpad2 => Some(2),
+        KeyCode::Digit3 | KeyCode::Numpad3 => Some(3),
+        KeyCode::Digit4 | KeyCode::Numpad4 => Some(4),
+        KeyCode::Digit5 | KeyCode::Numpad5 => Some(5),
+        KeyCode::Digit6 | KeyCode::Numpad6 => Some(6),
+        KeyCode::Digit7 | KeyCode::Numpad7 => Some(7),
+        KeyCode::Digit8 | KeyCode::Numpad8 => Some(8),
+        KeyCode::Digit9 | KeyCode::Numpad9 => Some(9),
+        _ => None,
+    }
+}
+
+/// キー入力でシード値を入力する関数
+/// 数字キーで末尾に1桁追加し、バックスペースキーで末尾の1桁を消す
+/// 全ての桁を消したらランダムに戻る
+fn key_seed_input(
+    mut seed: ResMut<Seed>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+) {
+    info_once!("key_seed_input");
+
+    for key in keyboard_input.get_just_pressed() {
+        if let Some(digit) = key_digit(*key) {
+            // 桁あふれする入力は無視する
+            let value = seed.fixed.unwrap_or(0).checked_mul(10).and_then(|value| value.checked_add(digit));
+            if value.is_some() {
+                seed.fixed = value;
+            }
+        } else if *key == KeyCode::Backspace && seed.fixed.is_some() {
+            seed.fixed = seed.fixed.map(|value| value / 10).filter(|value| *value > 0);
+        }
+    }
+}
+
+/// シード値の表示を更新する関数
+fn update_seed_text(
+    mut text_query: Query<&mut Text, With<SeedText>>,
+    seed: Res<Seed>,
+) {
+    info_once!("update_seed_text");
+
+    for mut text in &mut text_query {
+        **text = seed_text(&seed);
+    }
+}
+
 /// メインメニューのコンポーネントを全て削除する関数
 /// ステートがメインメニューから抜ける時に実行されます
 fn despawn(
@@ -237,7 +669,16 @@ impl Plugin for MainmenuPlugin {
     fn build(&self, app: &mut App) {
         app
             .add_systems(OnEnter(AppState::Mainmenu), setup)
-            .add_systems(Update, play_button_system.run_if(in_state(AppState::Mainmenu)))
+            .add_systems(Update, (
+                play_button_system,
+                mode_button_system,
+                option_button_system,
+                seed_button_system,
+                key_seed_input,
+                update_mode_text.run_if(resource_changed::<GameMode>),
+                update_option_text.run_if(resource_changed::<ModeOptions>.or(resource_changed::<GameMode>)),
+                update_seed_text.run_if(resource_changed::<Seed>),
+            ).run_if(in_state(AppState::Mainmenu)))
             .add_systems(OnExit(AppState::Mainmenu), despawn)
         ;
     }